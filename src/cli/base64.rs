@@ -1,8 +1,11 @@
-use std::str::FromStr;
+use std::{
+    io::{self, IsTerminal, Write},
+    str::FromStr,
+};
 
 use crate::{
-    process::{base64_decode, base64_encode},
-    utils::get_reader,
+    process::{base64_decode, base64_encode, parse_data_uri, sniff_mime, to_data_uri},
+    utils::{get_content, get_reader, get_writer},
     CmdExecutor,
 };
 
@@ -16,8 +19,14 @@ pub enum Base64SubCommands {
     #[command(name = "encode", about = "encode string to base64")]
     Base64Encode(Base64EncodeArgs),
 
-    #[command(name = "decode", about = "decode base64 to string")]
+    #[command(name = "decode", about = "decode base64 to text or a binary file")]
     Base64Decode(Base64DecodeArgs),
+
+    #[command(
+        name = "datauri",
+        about = "build a data URI from a file, or extract the payload of one"
+    )]
+    Base64DataUri(Base64DataUriArgs),
 }
 
 #[derive(Debug, Args)]
@@ -25,15 +34,22 @@ pub struct Base64EncodeArgs {
     #[arg(long,value_parser=verify_file,help="Input file")]
     pub input: String,
 
+    #[arg(short, long, help = "Output file, print to stdout if not set")]
+    pub output: Option<String>,
+
     #[arg(long,value_enum,default_value_t=Base64Format::Stand)]
     pub format: Base64Format,
+
+    #[arg(long,num_args=0..=1,default_missing_value="76",help="Wrap lines at this many columns [default: 76]")]
+    pub wrap: Option<usize>,
 }
 
 impl CmdExecutor for Base64EncodeArgs {
     async fn execute(self) -> anyhow::Result<()> {
         let mut input = get_reader(&self.input)?;
-        let encoded = base64_encode(&mut input, self.format)?;
-        println!("{}", encoded);
+        let mut output = get_writer(self.output.as_deref())?;
+        base64_encode(&mut input, &mut output, self.format, self.wrap)?;
+        writeln!(output)?;
         Ok(())
     }
 }
@@ -43,23 +59,83 @@ pub struct Base64DecodeArgs {
     #[arg(long,value_parser=verify_file,help="Input file")]
     pub input: String,
 
-    #[arg(long,value_enum,default_value_t=Base64Format::Stand)]
+    #[arg(short, long, help = "Output file, print to stdout if not set")]
+    pub output: Option<String>,
+
+    #[arg(long,value_enum,default_value_t=Base64Format::Auto)]
     pub format: Base64Format,
 }
 
 impl CmdExecutor for Base64DecodeArgs {
     async fn execute(self) -> anyhow::Result<()> {
         let mut input = get_reader(&self.input)?;
-        let decoded = base64_decode(&mut input, self.format)?;
+        if self.output.is_some() || !io::stdout().is_terminal() {
+            let mut output = get_writer(self.output.as_deref())?;
+            base64_decode(&mut input, &mut output, self.format)?;
+            return Ok(());
+        }
+        let mut decoded = Vec::new();
+        base64_decode(&mut input, &mut decoded, self.format)?;
+        let decoded = String::from_utf8(decoded).map_err(|_| {
+            anyhow::anyhow!("decoded data is binary, use --output or redirect stdout")
+        })?;
         println!("{}", decoded);
         Ok(())
     }
 }
 
+#[derive(Debug, Args)]
+pub struct Base64DataUriArgs {
+    #[arg(long,value_parser=verify_file,help="Input file, or a file holding a data URI with --decode")]
+    pub input: String,
+
+    #[arg(short, long, help = "Output file, print to stdout if not set")]
+    pub output: Option<String>,
+
+    #[arg(
+        long,
+        help = "MIME type, sniffed from the content or extension if not set"
+    )]
+    pub mime: Option<String>,
+
+    #[arg(long, default_value_t = false, conflicts_with = "mime")]
+    pub decode: bool,
+}
+
+impl CmdExecutor for Base64DataUriArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let content = get_content(&self.input)?;
+        if !self.decode {
+            let mime = match &self.mime {
+                Some(mime) => mime.as_str(),
+                None => sniff_mime(&content, Some(&self.input)),
+            };
+            let mut output = get_writer(self.output.as_deref())?;
+            writeln!(output, "{}", to_data_uri(&content, mime))?;
+            return Ok(());
+        }
+
+        let (mime, data) = parse_data_uri(&String::from_utf8(content)?)?;
+        eprintln!("mime : {}", mime);
+        if self.output.is_none() && io::stdout().is_terminal() {
+            let data = String::from_utf8(data).map_err(|_| {
+                anyhow::anyhow!("decoded data is binary, use --output or redirect stdout")
+            })?;
+            println!("{}", data);
+            return Ok(());
+        }
+        get_writer(self.output.as_deref())?.write_all(&data)?;
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum Base64Format {
     Stand,
     UrlSafe,
+    StandNoPad,
+    UrlSafeNoPad,
+    Auto,
 }
 
 impl FromStr for Base64Format {
@@ -68,6 +144,9 @@ impl FromStr for Base64Format {
         match s {
             "stand" => Ok(Base64Format::Stand),
             "urlsafe" => Ok(Base64Format::UrlSafe),
+            "standnopad" => Ok(Base64Format::StandNoPad),
+            "urlsafenopad" => Ok(Base64Format::UrlSafeNoPad),
+            "auto" => Ok(Base64Format::Auto),
             _ => Err(anyhow::anyhow!("invalid format {}", s)),
         }
     }
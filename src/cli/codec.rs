@@ -0,0 +1,76 @@
+use std::io::{self, IsTerminal, Write};
+
+use crate::{
+    process::get_codec,
+    utils::{get_content, get_writer},
+    CmdExecutor,
+};
+
+use super::verify_file;
+use clap::{Args, ValueEnum};
+
+#[derive(Debug, Args)]
+pub struct EncodeArgs {
+    #[arg(long,value_parser=verify_file,default_value="-",help="Input file")]
+    pub input: String,
+
+    #[arg(short, long, help = "Output file, print to stdout if not set")]
+    pub output: Option<String>,
+
+    #[arg(long, value_enum)]
+    pub codec: CodecKind,
+}
+
+impl CmdExecutor for EncodeArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let data = get_content(&self.input)?;
+        let encoded = get_codec(self.codec).encode(&data)?;
+        let mut output = get_writer(self.output.as_deref())?;
+        writeln!(output, "{}", encoded)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct DecodeArgs {
+    #[arg(long,value_parser=verify_file,default_value="-",help="Input file")]
+    pub input: String,
+
+    #[arg(short, long, help = "Output file, print to stdout if not set")]
+    pub output: Option<String>,
+
+    #[arg(long, value_enum)]
+    pub codec: CodecKind,
+}
+
+impl CmdExecutor for DecodeArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let text = String::from_utf8(get_content(&self.input)?)?;
+        let decoded = get_codec(self.codec).decode(text.trim_end_matches(['\r', '\n']))?;
+        if self.output.is_some() || !io::stdout().is_terminal() {
+            get_writer(self.output.as_deref())?.write_all(&decoded)?;
+            return Ok(());
+        }
+        let decoded = String::from_utf8(decoded).map_err(|_| {
+            anyhow::anyhow!("decoded data is binary, use --output or redirect stdout")
+        })?;
+        println!("{}", decoded);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CodecKind {
+    Hex,
+    Xxd,
+    Base32,
+    Base32Crockford,
+    Base58,
+    Base58check,
+    Base64,
+    Base64url,
+    Ascii85,
+    Z85,
+    Percent,
+    Html,
+}
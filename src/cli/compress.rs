@@ -0,0 +1,135 @@
+use std::io::{self, IsTerminal};
+
+use crate::{
+    process::{compress, decompress, train_dictionary},
+    utils::{get_content, get_reader, get_writer},
+    CmdExecutor,
+};
+
+use super::verify_file;
+use clap::{Args, Subcommand, ValueEnum};
+use enum_dispatch::enum_dispatch;
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct CompressArgs {
+    #[command(subcommand)]
+    pub command: Option<CompressSubCommands>,
+
+    #[arg(long,value_parser=verify_file,default_value="-",help="Input file")]
+    pub input: String,
+
+    #[arg(short, long, help = "Output file, print to stdout if not set")]
+    pub output: Option<String>,
+
+    #[arg(long,value_enum,default_value_t=CompressFormat::Zstd)]
+    pub format: CompressFormat,
+
+    #[arg(
+        short,
+        long,
+        allow_negative_numbers = true,
+        help = "Compression level, gzip/zlib/deflate/xz 0-9, brotli 0-11, zstd up to 22, negative zstd levels trade ratio for speed"
+    )]
+    pub level: Option<i32>,
+
+    #[arg(long,value_parser=verify_file,help="Zstd dictionary file")]
+    pub dict: Option<String>,
+}
+
+impl CmdExecutor for CompressArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        if let Some(command) = self.command {
+            return command.execute().await;
+        }
+
+        if self.output.is_none() && io::stdout().is_terminal() {
+            return Err(anyhow::anyhow!(
+                "output is binary, use --output or redirect stdout"
+            ));
+        }
+        let dict = self.dict.as_deref().map(get_content).transpose()?;
+        let stats = compress(
+            get_reader(&self.input)?,
+            get_writer(self.output.as_deref())?,
+            self.format,
+            self.level,
+            dict.as_deref(),
+        )?;
+        eprintln!("{}", stats);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Subcommand)]
+#[enum_dispatch(CmdExecutor)]
+pub enum CompressSubCommands {
+    #[command(name = "train", about = "Train a zstd dictionary from sample files")]
+    Train(CompressTrainArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct CompressTrainArgs {
+    #[arg(required = true, help = "Sample files")]
+    pub samples: Vec<String>,
+
+    #[arg(short, long)]
+    pub output: String,
+
+    #[arg(long, default_value_t = 112640)]
+    pub max_size: usize,
+}
+
+impl CmdExecutor for CompressTrainArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let dict = train_dictionary(&self.samples, self.max_size)?;
+        tokio::fs::write(&self.output, &dict).await?;
+        eprintln!("dictionary : {} bytes", dict.len());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct DecompressArgs {
+    #[arg(long,value_parser=verify_file,default_value="-",help="Input file")]
+    pub input: String,
+
+    #[arg(short, long, help = "Output file, print to stdout if not set")]
+    pub output: Option<String>,
+
+    #[arg(long, value_enum, help = "Detected from magic bytes if not set")]
+    pub format: Option<CompressFormat>,
+
+    #[arg(long,value_parser=verify_file,help="Zstd dictionary file")]
+    pub dict: Option<String>,
+}
+
+impl CmdExecutor for DecompressArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        if self.output.is_none() && io::stdout().is_terminal() {
+            return Err(anyhow::anyhow!(
+                "output may be binary, use --output or redirect stdout"
+            ));
+        }
+        let dict = self.dict.as_deref().map(get_content).transpose()?;
+        let stats = decompress(
+            get_reader(&self.input)?,
+            get_writer(self.output.as_deref())?,
+            self.format,
+            Some(&self.input),
+            dict.as_deref(),
+        )?;
+        eprintln!("{}", stats);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompressFormat {
+    Gzip,
+    Zlib,
+    Deflate,
+    Zstd,
+    Brotli,
+    Xz,
+}
@@ -1,15 +1,20 @@
-use crate::process::process_csv;
+use crate::process::{process_csv, process_csv_schema};
 use crate::CmdExecutor;
 
 use super::verify_file;
-use clap::{Args, ValueEnum};
+use clap::{Args, Subcommand, ValueEnum};
 use core::fmt;
+use enum_dispatch::enum_dispatch;
 use std::str::FromStr;
 
 #[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct CsvArgs {
-    #[arg(short,long,value_parser=verify_file)]
-    pub input: String,
+    #[command(subcommand)]
+    pub command: Option<CsvSubCommands>,
+
+    #[arg(short,long,value_parser=verify_file,required=true)]
+    pub input: Option<String>,
 
     #[arg(short, long)]
     pub output: Option<String>,
@@ -26,16 +31,72 @@ pub struct CsvArgs {
 
 impl CmdExecutor for CsvArgs {
     async fn execute(self) -> anyhow::Result<()> {
+        if let Some(command) = self.command {
+            return command.execute().await;
+        }
+        let input = self
+            .input
+            .ok_or_else(|| anyhow::anyhow!("input is required"))?;
         let output = if let Some(output) = self.output {
             output.clone()
         } else {
             format!("output.{}", self.format)
         };
-        process_csv(&self.input, &output, self.format)?;
+        process_csv(&input, &output, self.format)?;
         Ok(())
     }
 }
 
+#[derive(Debug, Subcommand)]
+#[enum_dispatch(CmdExecutor)]
+pub enum CsvSubCommands {
+    #[command(
+        name = "schema",
+        about = "Infer column types and emit a JSON Schema or Rust struct"
+    )]
+    Schema(CsvSchemaArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct CsvSchemaArgs {
+    #[arg(short,long,value_parser=verify_file)]
+    pub input: String,
+
+    #[arg(short, long, help = "Output file, print to stdout if not set")]
+    pub output: Option<String>,
+
+    #[arg(long,value_enum,default_value_t=SchemaFormat::JsonSchema)]
+    pub format: SchemaFormat,
+
+    #[arg(short, long, default_value_t = ',')]
+    pub delimiter: char,
+
+    #[arg(
+        long,
+        default_value_t = 1000,
+        help = "Number of rows to sample, 0 for all rows"
+    )]
+    pub sample: usize,
+
+    #[arg(long, default_value = "Record", help = "Title or struct name")]
+    pub name: String,
+}
+
+impl CmdExecutor for CsvSchemaArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let delimiter = u8::try_from(self.delimiter)
+            .map_err(|_| anyhow::anyhow!("delimiter must be an ascii character"))?;
+        process_csv_schema(
+            &self.input,
+            self.output.as_deref(),
+            self.format,
+            delimiter,
+            self.sample,
+            &self.name,
+        )
+    }
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum OutputFormat {
     JSON,
@@ -67,3 +128,9 @@ impl FromStr for OutputFormat {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SchemaFormat {
+    JsonSchema,
+    Rust,
+}
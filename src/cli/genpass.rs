@@ -1,11 +1,85 @@
-use crate::process::genpass;
+use crate::process::{
+    audit_passwords, audit_table, check_strength, default_wordlist, derive_password,
+    format_entries, generate_password, generate_pronounceable, genphrase, parse_wordlist, HibpFile,
+    PasswordEntry, PasswordPolicy, StrengthReport,
+};
+use crate::utils::{get_content, read_secret};
 use crate::CmdExecutor;
-use clap::Args;
+use clap::{Args, Subcommand, ValueEnum};
+use enum_dispatch::enum_dispatch;
+
+use super::verify_file;
 
 #[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct GenPassArgs {
-    #[arg(short, long, default_value_t = 8)]
-    pub len: u8,
+    #[command(subcommand)]
+    pub command: Option<GenPassSubCommands>,
+
+    #[command(flatten)]
+    pub policy: PolicyArgs,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with = "words",
+        help = "Build the password from consonant-vowel syllables"
+    )]
+    pub pronounceable: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "len",
+        help = "Generate a passphrase of this many words instead, 11 bits per word with the built-in BIP-39 list"
+    )]
+    pub words: Option<u8>,
+
+    #[arg(long,value_parser=verify_file,requires="words",help="Wordlist file, one word per line or diceware format")]
+    pub wordlist: Option<String>,
+
+    #[arg(long, default_value = "-", requires = "words")]
+    pub separator: String,
+
+    #[arg(long, default_value_t = false, requires = "words")]
+    pub capitalize: bool,
+
+    #[arg(long, default_value_t = false, requires = "words")]
+    pub append_number: bool,
+
+    #[arg(long, default_value_t = false, requires = "words")]
+    pub append_symbol: bool,
+
+    #[arg(long,default_value_t=0,value_parser=clap::value_parser!(u8).range(0..=4),help="Regenerate until the zxcvbn score reaches this value")]
+    pub min_score: u8,
+
+    #[arg(
+        short,
+        long,
+        default_value_t = false,
+        help = "Print guesses, crack times and feedback"
+    )]
+    pub verbose: bool,
+
+    #[arg(short, long, default_value_t = 1)]
+    pub count: usize,
+
+    #[arg(long,value_parser=verify_file,help="Sorted Pwned Passwords SHA-1 file, reject passwords found in it")]
+    pub hibp_file: Option<String>,
+
+    #[arg(long,value_enum,default_value_t=GenPassFormat::Plain)]
+    pub format: GenPassFormat,
+
+    #[arg(
+        long,
+        help = "Label prefix, entries are labeled <label>1, <label>2, ..."
+    )]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct PolicyArgs {
+    #[arg(short, long, help = "Password length [default: 8]")]
+    pub len: Option<u16>,
 
     #[arg(long, default_value_t = false)]
     pub no_upper: bool,
@@ -18,18 +92,334 @@ pub struct GenPassArgs {
 
     #[arg(long, default_value_t = false)]
     pub no_symbol: bool,
+
+    #[arg(long)]
+    pub min_upper: Option<u16>,
+
+    #[arg(long)]
+    pub min_lower: Option<u16>,
+
+    #[arg(long)]
+    pub min_number: Option<u16>,
+
+    #[arg(long)]
+    pub min_symbol: Option<u16>,
+
+    #[arg(long, help = "Custom symbol set")]
+    pub symbols: Option<String>,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Exclude ambiguous characters 0O1lI"
+    )]
+    pub exclude_ambiguous: bool,
+
+    #[arg(long, help = "Characters to exclude")]
+    pub exclude: Option<String>,
+
+    #[arg(long, help = "Max consecutive repeats of the same character")]
+    pub max_repeat: Option<u16>,
+
+    #[arg(long,value_parser=verify_file,help="YAML password policy profile, overridden by flags")]
+    pub profile: Option<String>,
 }
+
+impl PolicyArgs {
+    pub fn to_policy(&self) -> anyhow::Result<PasswordPolicy> {
+        let mut policy = match &self.profile {
+            Some(path) => PasswordPolicy::from_yaml(&String::from_utf8(get_content(path)?)?)?,
+            None => PasswordPolicy::default(),
+        };
+        if let Some(len) = self.len {
+            policy.length = len;
+        }
+        policy.upper &= !self.no_upper;
+        policy.lower &= !self.no_lower;
+        policy.number &= !self.no_number;
+        policy.symbol &= !self.no_symbol;
+        if let Some(min) = self.min_upper {
+            policy.min_upper = min;
+        }
+        if let Some(min) = self.min_lower {
+            policy.min_lower = min;
+        }
+        if let Some(min) = self.min_number {
+            policy.min_number = min;
+        }
+        if let Some(min) = self.min_symbol {
+            policy.min_symbol = min;
+        }
+        if self.symbols.is_some() {
+            policy.symbols = self.symbols.clone();
+        }
+        policy.exclude_ambiguous |= self.exclude_ambiguous;
+        if self.exclude.is_some() {
+            policy.exclude = self.exclude.clone();
+        }
+        if self.max_repeat.is_some() {
+            policy.max_repeat = self.max_repeat;
+        }
+        policy.validate()?;
+        Ok(policy)
+    }
+}
+
+const MAX_ATTEMPTS: usize = 1000;
+
+impl GenPassArgs {
+    fn generate(
+        &self,
+        policy: &PasswordPolicy,
+        wordlist: Option<&[String]>,
+    ) -> anyhow::Result<(String, f64)> {
+        match (self.words, wordlist) {
+            (Some(words), Some(wordlist)) => {
+                let (phrase, entropy) = genphrase(
+                    wordlist,
+                    words,
+                    &self.separator,
+                    self.capitalize,
+                    self.append_number,
+                    self.append_symbol,
+                )?;
+                Ok((phrase, entropy))
+            }
+            _ if self.pronounceable => generate_pronounceable(policy),
+            _ => {
+                let password = generate_password(policy)?;
+                Ok((password, policy.entropy_bits()?))
+            }
+        }
+    }
+
+    fn generate_strong(
+        &self,
+        policy: &PasswordPolicy,
+        wordlist: Option<&[String]>,
+        mut hibp: Option<&mut HibpFile>,
+    ) -> anyhow::Result<(String, f64, StrengthReport)> {
+        let mut breached = 0;
+        for _ in 0..MAX_ATTEMPTS {
+            let (password, entropy) = self.generate(policy, wordlist)?;
+            let report = check_strength(&password, &[]);
+            if report.score < self.min_score {
+                continue;
+            }
+            if let Some(hibp) = hibp.as_deref_mut() {
+                if hibp.lookup(&password)? > 0 {
+                    breached += 1;
+                    continue;
+                }
+            }
+            return Ok((password, entropy, report));
+        }
+        match breached {
+            0 => Err(anyhow::anyhow!(
+                "no password reached score {} after {} attempts, try a longer length",
+                self.min_score,
+                MAX_ATTEMPTS
+            )),
+            MAX_ATTEMPTS => Err(anyhow::anyhow!(
+                "all {} candidates were found in the HIBP list, try a longer length or a larger character set",
+                MAX_ATTEMPTS
+            )),
+            _ => Err(anyhow::anyhow!(
+                "no usable password after {} attempts: {} scored below {}, {} were found in the HIBP list",
+                MAX_ATTEMPTS,
+                MAX_ATTEMPTS - breached,
+                self.min_score,
+                breached
+            )),
+        }
+    }
+}
+
 impl CmdExecutor for GenPassArgs {
     async fn execute(self) -> anyhow::Result<()> {
-        let password = genpass(
-            self.len,
-            self.no_upper,
-            self.no_lower,
-            self.no_number,
-            self.no_symbol,
+        if let Some(command) = self.command {
+            return command.execute().await;
+        }
+
+        let wordlist = match (&self.words, &self.wordlist) {
+            (Some(_), Some(path)) => Some(parse_wordlist(&String::from_utf8(get_content(path)?)?)?),
+            (Some(_), None) => Some(default_wordlist()),
+            _ => None,
+        };
+        let policy = self.policy.to_policy()?;
+        let mut hibp = self.hibp_file.as_deref().map(HibpFile::open).transpose()?;
+
+        let mut entries = Vec::with_capacity(self.count);
+        for i in 1..=self.count {
+            let (password, entropy_bits, report) =
+                self.generate_strong(&policy, wordlist.as_deref(), hibp.as_mut())?;
+            if self.verbose {
+                eprintln!("{}", report);
+            }
+            entries.push(PasswordEntry {
+                label: self.label.as_ref().map(|label| format!("{}{}", label, i)),
+                password,
+                score: report.score,
+                entropy_bits,
+            });
+        }
+
+        println!("{}", format_entries(&entries, self.format)?);
+        if let [entry] = entries.as_slice() {
+            if !self.verbose {
+                eprintln!("score : {}", entry.score);
+            }
+            eprintln!("entropy : {:.1} bits", entry.entropy_bits);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Subcommand)]
+#[enum_dispatch(CmdExecutor)]
+pub enum GenPassSubCommands {
+    #[command(name = "check", about = "Audit the strength of existing passwords")]
+    Check(GenPassCheckArgs),
+
+    #[command(
+        name = "derive",
+        about = "Derive a stable site password from a master passphrase"
+    )]
+    Derive(GenPassDeriveArgs),
+
+    #[command(
+        name = "breached",
+        about = "Look up passwords in a local Pwned Passwords file"
+    )]
+    Breached(GenPassBreachedArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct GenPassCheckArgs {
+    #[arg(long,value_parser=verify_file,help="File with one password per line, prompt if not set and stdin is a terminal")]
+    pub input: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        value_delimiter = ',',
+        help = "Context words such as username or site"
+    )]
+    pub context: Vec<String>,
+
+    #[arg(long,value_enum,default_value_t=AuditFormat::Table)]
+    pub format: AuditFormat,
+
+    #[arg(long, default_value_t = false)]
+    pub show_password: bool,
+
+    #[arg(long,value_parser=verify_file,help="Sorted Pwned Passwords SHA-1 file to report breach counts")]
+    pub hibp_file: Option<String>,
+}
+
+impl CmdExecutor for GenPassCheckArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let content = read_secret(self.input.as_deref(), "Password: ")?;
+        let context = self.context.iter().map(|c| c.as_str()).collect::<Vec<_>>();
+        let mut hibp = self.hibp_file.as_deref().map(HibpFile::open).transpose()?;
+        let entries = audit_passwords(&content, &context, self.show_password, hibp.as_mut())?;
+        match self.format {
+            AuditFormat::Table => println!("{}", audit_table(&entries)),
+            AuditFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+        }
+        let rejected = entries.iter().filter(|e| e.rejected).count();
+        if rejected > 0 {
+            return Err(anyhow::anyhow!(
+                "{} of {} passwords were found in the HIBP list",
+                rejected,
+                entries.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct GenPassDeriveArgs {
+    #[arg(long)]
+    pub site: String,
+
+    #[arg(long, default_value = "")]
+    pub login: String,
+
+    #[arg(long, default_value_t = 1)]
+    pub counter: u32,
+
+    #[arg(long,value_enum,default_value_t=DeriveKdf::Argon2id)]
+    pub kdf: DeriveKdf,
+
+    #[arg(long,value_parser=verify_file,help="Master passphrase file, prompt if not set and stdin is a terminal")]
+    pub master: Option<String>,
+
+    #[command(flatten)]
+    pub policy: PolicyArgs,
+}
+
+impl CmdExecutor for GenPassDeriveArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let master = read_secret(self.master.as_deref(), "Master passphrase: ")?;
+        let master = master.trim_end_matches(['\r', '\n']);
+        if master.is_empty() {
+            return Err(anyhow::anyhow!("master passphrase must not be empty"));
+        }
+        let policy = self.policy.to_policy()?;
+        let password = derive_password(
+            master,
+            &self.site,
+            &self.login,
+            self.counter,
+            self.kdf,
+            &policy,
         )?;
         println!("{}", password);
-        eprintln!("score : {}", zxcvbn::zxcvbn(&password, &[]).score());
         Ok(())
     }
 }
+
+#[derive(Debug, Args)]
+pub struct GenPassBreachedArgs {
+    #[arg(long,value_parser=verify_file,help="Sorted Pwned Passwords SHA-1 file (ordered by hash)")]
+    pub hibp_file: String,
+
+    #[arg(long,value_parser=verify_file,help="File with one password per line, prompt if not set and stdin is a terminal")]
+    pub input: Option<String>,
+}
+
+impl CmdExecutor for GenPassBreachedArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let content = read_secret(self.input.as_deref(), "Password: ")?;
+        let mut hibp = HibpFile::open(&self.hibp_file)?;
+        for password in content
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .filter(|line| !line.is_empty())
+        {
+            println!("{}", hibp.lookup(password)?);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DeriveKdf {
+    Argon2id,
+    Blake3,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum AuditFormat {
+    Table,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum GenPassFormat {
+    Plain,
+    Json,
+    Csv,
+}
@@ -1,17 +1,29 @@
 pub mod base64;
+pub mod codec;
+pub mod compress;
 pub mod csv;
 pub mod genpass;
 pub mod http;
+pub mod otp;
+pub mod pipe;
+pub mod random;
 pub mod text;
+pub mod token;
 
 pub use base64::Base64SubCommands;
 use clap::{Parser, Subcommand};
+use codec::{DecodeArgs, EncodeArgs};
+use compress::{CompressArgs, DecompressArgs};
 use csv::CsvArgs;
 use enum_dispatch::enum_dispatch;
 use genpass::GenPassArgs;
 use http::HttpSubCommands;
+use otp::OtpSubCommands;
+use pipe::PipeArgs;
+use random::RandomSubCommands;
 use std::path::{Path, PathBuf};
 use text::TextSubCommands;
+use token::TokenSubCommands;
 
 #[derive(Debug, Parser)]
 #[command(name="rcli",version, about, long_about = None)]
@@ -19,10 +31,18 @@ use text::TextSubCommands;
 pub struct Cli {
     #[command(subcommand)]
     pub command: SubCommands,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Seed passwords, tokens and random data (and pin UUIDv7/ULID timestamps) for reproducible test fixtures, NEVER for real secrets; keys and OTP secrets always use the OS RNG"
+    )]
+    pub seed: Option<String>,
 }
 
 #[derive(Debug, Subcommand)]
 #[enum_dispatch(CmdExecutor)]
+#[allow(clippy::large_enum_variant)]
 pub enum SubCommands {
     #[command(name = "csv", about = "Show CSV, or convert CSV to other formats")]
     Csv(CsvArgs),
@@ -33,11 +53,44 @@ pub enum SubCommands {
     #[command(subcommand, about = "Base64 encode or decode")]
     Base64(Base64SubCommands),
 
+    #[command(
+        name = "encode",
+        about = "Encode with hex, base32, base58, base64, ascii85, percent or HTML entities"
+    )]
+    Encode(EncodeArgs),
+
+    #[command(name = "decode", about = "Decode any of the encode codecs")]
+    Decode(DecodeArgs),
+
+    #[command(
+        name = "pipe",
+        about = "Chain compression and encoding steps, e.g. gzip,base64url"
+    )]
+    Pipe(PipeArgs),
+
+    #[command(name = "compress", about = "Compress with gzip, zstd, brotli or xz")]
+    Compress(CompressArgs),
+
+    #[command(
+        name = "decompress",
+        about = "Decompress gzip, zstd, brotli or xz, detecting the format"
+    )]
+    Decompress(DecompressArgs),
+
     #[command(subcommand, about = "Sign or verify text")]
     Text(TextSubCommands),
 
     #[command(subcommand)]
     Http(HttpSubCommands),
+
+    #[command(subcommand, about = "Generate or verify HOTP/TOTP one-time passwords")]
+    Otp(OtpSubCommands),
+
+    #[command(subcommand, about = "Generate UUIDs, ULIDs, NanoIDs or API keys")]
+    Token(TokenSubCommands),
+
+    #[command(subcommand, about = "Random bytes, numbers, lines and dice rolls")]
+    Random(RandomSubCommands),
 }
 
 fn verify_file(filename: &str) -> Result<String, &'static str> {
@@ -67,4 +120,10 @@ mod tests {
         assert!(verify_file("Cargo.toml").is_ok());
         assert_eq!(verify_file("not-exist-file"), Err("File does not exist"));
     }
+
+    #[test]
+    fn test_cli_definition() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
 }
@@ -0,0 +1,197 @@
+use crate::{
+    process::{
+        decode_secret, generate_secret, hotp, otpauth_uri, totp, unix_time, verify_hotp,
+        verify_totp,
+    },
+    CmdExecutor,
+};
+
+use clap::{Args, Subcommand, ValueEnum};
+use enum_dispatch::enum_dispatch;
+use std::fmt;
+
+#[derive(Debug, Subcommand)]
+#[enum_dispatch(CmdExecutor)]
+pub enum OtpSubCommands {
+    #[command(name = "secret", about = "generate a random base32 secret")]
+    OtpSecret(OtpSecretArgs),
+
+    #[command(name = "uri", about = "print an otpauth:// URI for authenticator apps")]
+    OtpUri(OtpUriArgs),
+
+    #[command(
+        name = "code",
+        about = "compute the current TOTP, or a HOTP with --counter"
+    )]
+    OtpCode(OtpCodeArgs),
+
+    #[command(name = "verify", about = "verify a code within a skew window")]
+    OtpVerify(OtpVerifyArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct OtpSecretArgs {
+    #[arg(long, default_value_t = 20, help = "Secret length in bytes")]
+    pub bytes: usize,
+}
+
+impl CmdExecutor for OtpSecretArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        if self.bytes < 10 {
+            return Err(anyhow::anyhow!("secret should be at least 10 bytes"));
+        }
+        println!("{}", generate_secret(self.bytes));
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct OtpParams {
+    #[arg(long, help = "Base32 encoded secret")]
+    pub secret: String,
+
+    #[arg(long,value_enum,default_value_t=OtpAlgorithm::Sha1)]
+    pub algorithm: OtpAlgorithm,
+
+    #[arg(long,default_value_t=6,value_parser=clap::value_parser!(u32).range(6..=8))]
+    pub digits: u32,
+
+    #[arg(long,default_value_t=30,value_parser=clap::value_parser!(u64).range(1..),help="TOTP time step in seconds")]
+    pub period: u64,
+
+    #[arg(long, help = "Use HOTP with this counter instead of TOTP")]
+    pub counter: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+pub struct OtpUriArgs {
+    #[command(flatten)]
+    pub params: OtpParams,
+
+    #[arg(long)]
+    pub issuer: Option<String>,
+
+    #[arg(long)]
+    pub account: String,
+}
+
+impl CmdExecutor for OtpUriArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let secret = decode_secret(&self.params.secret)?;
+        let uri = otpauth_uri(
+            &secret,
+            self.issuer.as_deref(),
+            &self.account,
+            self.params.algorithm,
+            self.params.digits,
+            self.params.period,
+            self.params.counter,
+        );
+        println!("{}", uri);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct OtpCodeArgs {
+    #[command(flatten)]
+    pub params: OtpParams,
+
+    #[arg(long, help = "Unix time to use instead of now")]
+    pub time: Option<u64>,
+}
+
+impl CmdExecutor for OtpCodeArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let p = self.params;
+        let secret = decode_secret(&p.secret)?;
+        let code = match p.counter {
+            Some(counter) => hotp(&secret, counter, p.digits, p.algorithm)?,
+            None => {
+                let time = match self.time {
+                    Some(time) => time,
+                    None => unix_time()?,
+                };
+                eprintln!("expires in : {}s", p.period - time % p.period);
+                totp(&secret, time, p.period, p.digits, p.algorithm)?
+            }
+        };
+        println!("{}", code);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct OtpVerifyArgs {
+    #[command(flatten)]
+    pub params: OtpParams,
+
+    #[arg(long)]
+    pub code: String,
+
+    #[arg(
+        long,
+        default_value_t = 1,
+        help = "Accepted time steps before and after now, or counters ahead for HOTP"
+    )]
+    pub skew: u64,
+
+    #[arg(long, help = "Unix time to use instead of now")]
+    pub time: Option<u64>,
+}
+
+impl CmdExecutor for OtpVerifyArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let p = self.params;
+        let secret = decode_secret(&p.secret)?;
+        let code = self.code.trim();
+        let r = match p.counter {
+            Some(counter) => {
+                let matched =
+                    verify_hotp(&secret, code, counter, self.skew, p.digits, p.algorithm)?;
+                if let Some(matched) = matched {
+                    eprintln!("counter : {}", matched);
+                }
+                matched.is_some()
+            }
+            None => {
+                let time = match self.time {
+                    Some(time) => time,
+                    None => unix_time()?,
+                };
+                let matched = verify_totp(
+                    &secret,
+                    code,
+                    time,
+                    p.period,
+                    self.skew,
+                    p.digits,
+                    p.algorithm,
+                )?;
+                if let Some(offset) = matched {
+                    eprintln!("offset : {} step(s)", offset);
+                }
+                matched.is_some()
+            }
+        };
+        println!("{}", r);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum OtpAlgorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl fmt::Display for OtpAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OtpAlgorithm::Sha1 => write!(f, "SHA1"),
+            OtpAlgorithm::Sha256 => write!(f, "SHA256"),
+            OtpAlgorithm::Sha512 => write!(f, "SHA512"),
+        }
+    }
+}
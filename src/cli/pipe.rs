@@ -0,0 +1,66 @@
+use std::io::{self, IsTerminal};
+
+use crate::{
+    process::{pipe_forward, pipe_reverse},
+    utils::{get_reader, get_writer},
+    CmdExecutor,
+};
+
+use super::verify_file;
+use clap::{Args, ValueEnum};
+
+#[derive(Debug, Args)]
+pub struct PipeArgs {
+    #[arg(long,value_parser=verify_file,default_value="-",help="Input file")]
+    pub input: String,
+
+    #[arg(short, long, help = "Output file, print to stdout if not set")]
+    pub output: Option<String>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        required = true,
+        help = "Steps applied in order, e.g. gzip,base64url"
+    )]
+    pub steps: Vec<PipeStep>,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Undo the steps, last step first"
+    )]
+    pub reverse: bool,
+}
+
+impl CmdExecutor for PipeArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let input = get_reader(&self.input)?;
+        if self.reverse {
+            pipe_reverse(input, get_writer(self.output.as_deref())?, &self.steps)?;
+            return Ok(());
+        }
+
+        let text = self.steps.last().is_some_and(|step| step.is_encoding());
+        if !text && self.output.is_none() && io::stdout().is_terminal() {
+            return Err(anyhow::anyhow!(
+                "output is binary, use --output, redirect stdout or end with an encoding step"
+            ));
+        }
+        pipe_forward(input, get_writer(self.output.as_deref())?, &self.steps)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum PipeStep {
+    Gzip,
+    Zlib,
+    Deflate,
+    Zstd,
+    Brotli,
+    Xz,
+    Base64,
+    Base64url,
+}
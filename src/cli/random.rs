@@ -0,0 +1,149 @@
+use crate::{
+    process::{pick_lines, random_bytes, random_floats, random_ints, roll_dice, shuffle_lines},
+    utils::get_content,
+    CmdExecutor,
+};
+
+use super::verify_file;
+use clap::{Args, Subcommand, ValueEnum};
+use enum_dispatch::enum_dispatch;
+
+#[derive(Debug, Subcommand)]
+#[enum_dispatch(CmdExecutor)]
+pub enum RandomSubCommands {
+    #[command(name = "bytes", about = "random bytes as hex or base64")]
+    RandomBytes(RandomBytesArgs),
+
+    #[command(name = "int", about = "uniform integers in an inclusive range")]
+    RandomInt(RandomIntArgs),
+
+    #[command(name = "float", about = "uniform floats in a half-open range")]
+    RandomFloat(RandomFloatArgs),
+
+    #[command(name = "pick", about = "pick random lines from the input")]
+    RandomPick(RandomPickArgs),
+
+    #[command(name = "shuffle", about = "shuffle the lines of the input")]
+    RandomShuffle(RandomShuffleArgs),
+
+    #[command(name = "dice", about = "roll dice, e.g. 3d6+2")]
+    RandomDice(RandomDiceArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct RandomBytesArgs {
+    #[arg(long, default_value_t = 32)]
+    pub bytes: usize,
+
+    #[arg(long,value_enum,default_value_t=BytesEncoding::Hex)]
+    pub encoding: BytesEncoding,
+}
+
+impl CmdExecutor for RandomBytesArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        println!("{}", random_bytes(self.bytes, self.encoding));
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct RandomIntArgs {
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    pub min: i64,
+
+    #[arg(long, default_value_t = 100, allow_negative_numbers = true)]
+    pub max: i64,
+
+    #[arg(short, long, default_value_t = 1)]
+    pub count: usize,
+}
+
+impl CmdExecutor for RandomIntArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        for n in random_ints(self.min, self.max, self.count)? {
+            println!("{}", n);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct RandomFloatArgs {
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    pub min: f64,
+
+    #[arg(long, default_value_t = 1.0, allow_negative_numbers = true)]
+    pub max: f64,
+
+    #[arg(short, long, default_value_t = 1)]
+    pub count: usize,
+}
+
+impl CmdExecutor for RandomFloatArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        for n in random_floats(self.min, self.max, self.count)? {
+            println!("{}", n);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct RandomPickArgs {
+    #[arg(long,value_parser=verify_file,default_value="-")]
+    pub input: String,
+
+    #[arg(short, long, default_value_t = 1)]
+    pub count: usize,
+}
+
+impl CmdExecutor for RandomPickArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let content = String::from_utf8(get_content(&self.input)?)?;
+        for line in pick_lines(&content, self.count) {
+            println!("{}", line);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct RandomShuffleArgs {
+    #[arg(long,value_parser=verify_file,default_value="-")]
+    pub input: String,
+}
+
+impl CmdExecutor for RandomShuffleArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let content = String::from_utf8(get_content(&self.input)?)?;
+        for line in shuffle_lines(&content) {
+            println!("{}", line);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct RandomDiceArgs {
+    #[arg(
+        allow_hyphen_values = true,
+        help = "Dice notation, e.g. 3d6+2 or -2+1d6"
+    )]
+    pub notation: String,
+}
+
+impl CmdExecutor for RandomDiceArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let (total, rolls) = roll_dice(&self.notation)?;
+        println!("{}", total);
+        eprintln!("rolls : {:?}", rolls);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum BytesEncoding {
+    Hex,
+    Base64,
+    Base64url,
+}
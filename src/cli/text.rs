@@ -1,6 +1,13 @@
 use crate::{
-    process::text::{decrypt_text, encrypt_text, key_generate, sign_text, verify_text},
-    utils::{get_content, get_reader},
+    process::{
+        dearmor,
+        text::{
+            decrypt_text, encrypt_text, export_ed25519, key_derive, key_generate, sign_text,
+            verify_text, KeyFile,
+        },
+        unix_time, Armor, DetachedSignature,
+    },
+    utils::{get_content, get_reader, get_writer},
     CmdExecutor,
 };
 
@@ -8,8 +15,13 @@ use super::{verify_file, verify_path};
 use base64::prelude::*;
 use clap::{Args, Subcommand, ValueEnum};
 use enum_dispatch::enum_dispatch;
-use std::{io::Cursor, path::PathBuf, str::FromStr};
-use tokio::fs;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{Cursor, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 #[derive(Debug, Subcommand)]
 #[enum_dispatch(CmdExecutor)]
@@ -26,6 +38,18 @@ pub enum TextSubCommands {
     #[command(name = "generate")]
     KeyGenerate(KeyGenerateArgs),
 
+    #[command(
+        name = "export",
+        about = "convert an ed25519 private key to raw, PKCS#8 PEM or OpenSSH files"
+    )]
+    KeyExport(KeyExportArgs),
+
+    #[command(
+        name = "derive",
+        about = "derive a purpose-specific blake3 subkey from a master key"
+    )]
+    KeyDerive(KeyDeriveArgs),
+
     #[command(name = "encrypt")]
     TextEncrypt(TextEncryptArgs),
 
@@ -33,6 +57,34 @@ pub enum TextSubCommands {
     TextDecrypt(TextDecryptArgs),
 }
 
+const ED25519_PRIVATE: &str = "ED25519 PRIVATE KEY";
+const ED25519_PUBLIC: &str = "ED25519 PUBLIC KEY";
+const BLAKE3_KEY: &str = "BLAKE3 KEY";
+
+fn key_label(format: &TextSignFormat, private: bool) -> &'static str {
+    match format {
+        TextSignFormat::Blake3 => BLAKE3_KEY,
+        _ if private => ED25519_PRIVATE,
+        _ => ED25519_PUBLIC,
+    }
+}
+
+// raw key bytes, or the payload of an armored key file labeled `label`
+fn read_key(path: &str, label: &str) -> anyhow::Result<Vec<u8>> {
+    let content = get_content(path)?;
+    match dearmor(&content)? {
+        Some(armor) => armor.into_data(label),
+        None => Ok(content),
+    }
+}
+
+fn decode_armored(content: &[u8], label: &str) -> anyhow::Result<Vec<u8>> {
+    match dearmor(content)? {
+        Some(armor) => armor.into_data(label),
+        None => Ok(BASE64_URL_SAFE_NO_PAD.decode(content.trim_ascii())?),
+    }
+}
+
 #[derive(Debug, Args)]
 pub struct TextDecryptArgs {
     #[arg(long)]
@@ -45,7 +97,7 @@ pub struct TextDecryptArgs {
 impl CmdExecutor for TextDecryptArgs {
     async fn execute(self) -> anyhow::Result<()> {
         let content = get_content(&self.input)?;
-        let content = BASE64_URL_SAFE_NO_PAD.decode(content)?;
+        let content = decode_armored(&content, "MESSAGE")?;
         let plain = decrypt_text(&mut Cursor::new(&content), &self.key)?;
         println!("{}", String::from_utf8(plain)?);
         Ok(())
@@ -59,12 +111,20 @@ pub struct TextEncryptArgs {
 
     #[arg(long,value_parser=verify_file,default_value="-")]
     pub input: String,
+
+    #[arg(long, default_value_t = false, help = "PEM-style armored output")]
+    pub armor: bool,
 }
 
 impl CmdExecutor for TextEncryptArgs {
     async fn execute(self) -> anyhow::Result<()> {
         let mut reader = get_reader(&self.input)?;
         let encrypt = encrypt_text(&mut reader, &self.key)?;
+        if self.armor {
+            let armor = Armor::new("MESSAGE", encrypt).header("Cipher", "ChaCha20-Poly1305");
+            println!("{}", armor);
+            return Ok(());
+        }
         let encrtpt = BASE64_URL_SAFE_NO_PAD.encode(encrypt);
         println!("{}", encrtpt);
         Ok(())
@@ -78,18 +138,112 @@ pub struct KeyGenerateArgs {
 
     #[arg(long,value_parser=verify_path)]
     pub output: PathBuf,
+
+    #[arg(long,value_enum,default_value_t=KeyFormat::Raw)]
+    pub key_format: KeyFormat,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Write PEM-style armored key files"
+    )]
+    pub armor: bool,
 }
 
 impl CmdExecutor for KeyGenerateArgs {
     async fn execute(self) -> anyhow::Result<()> {
-        let keys = key_generate(self.format)?;
-        for (k, v) in keys {
-            fs::write(self.output.join(k), v).await?;
+        if self.armor && self.key_format != KeyFormat::Raw {
+            return Err(anyhow::anyhow!("--armor only applies to raw keys"));
+        }
+        let keys = key_generate(self.format.clone(), self.key_format)?;
+        for (k, mut file) in keys {
+            if self.armor {
+                let label = match k {
+                    "ed25519.sk" => ED25519_PRIVATE,
+                    "ed25519.pk" => ED25519_PUBLIC,
+                    _ => BLAKE3_KEY,
+                };
+                let armor = Armor::new(label, file.data).header("Algorithm", &self.format);
+                file.data = format!("{}\n", armor).into_bytes();
+            }
+            write_key(&self.output.join(k), &file)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct KeyExportArgs {
+    #[arg(long,value_parser=verify_file,help="Ed25519 private key in any supported format")]
+    pub key: String,
+
+    #[arg(long, value_enum)]
+    pub key_format: KeyFormat,
+
+    #[arg(long,value_parser=verify_path)]
+    pub output: PathBuf,
+}
+
+impl CmdExecutor for KeyExportArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let key = read_key(&self.key, ED25519_PRIVATE)?;
+        for (k, file) in export_ed25519(&key, self.key_format)? {
+            write_key(&self.output.join(k), &file)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct KeyDeriveArgs {
+    #[arg(long, help = "Unique context string, e.g. \"app 2026 session\"")]
+    pub context: String,
+
+    #[arg(long,value_parser=verify_file,help="Blake3 master key")]
+    pub from: String,
+
+    #[arg(short, long, help = "Output file, print to stdout if not set")]
+    pub output: Option<PathBuf>,
+}
+
+impl CmdExecutor for KeyDeriveArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let master = read_key(&self.from, BLAKE3_KEY)?;
+        let subkey = key_derive(&master, &self.context)?;
+        match self.output {
+            Some(path) => write_key(
+                &path,
+                &KeyFile {
+                    data: subkey.into_bytes(),
+                    private: true,
+                },
+            )?,
+            None => println!("{}", subkey),
         }
         Ok(())
     }
 }
 
+// private key files are only readable by the owner, ssh refuses them otherwise
+fn write_key(path: &Path, file: &KeyFile) -> anyhow::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if file.private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut out = options.open(path)?;
+    // the mode only applies to new files, tighten an existing one before writing
+    #[cfg(unix)]
+    if file.private {
+        use std::os::unix::fs::PermissionsExt;
+        out.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    out.write_all(&file.data)?;
+    Ok(())
+}
+
 #[derive(Debug, Args)]
 pub struct TextVerifyArgs {
     #[arg(long,value_parser=verify_file,default_value="-")]
@@ -98,19 +252,55 @@ pub struct TextVerifyArgs {
     #[arg(long,value_parser=verify_file)]
     pub key: String,
 
-    #[arg(long)]
+    #[arg(long, help = "Signature, or a signature file (bare, JSON or PEM)")]
     pub sig: String,
 
-    #[arg(long, value_enum,default_value_t=TextSignFormat::Blake3)]
-    pub format: TextSignFormat,
+    #[arg(
+        long,
+        value_enum,
+        help = "Taken from the signature file if not set [default: blake3]"
+    )]
+    pub format: Option<TextSignFormat>,
 }
 
 impl CmdExecutor for TextVerifyArgs {
     async fn execute(self) -> anyhow::Result<()> {
         let mut input = get_reader(&self.input)?;
-        let sig = BASE64_URL_SAFE_NO_PAD.decode(&self.sig)?;
-        let key = get_content(&self.key)?;
-        let r = verify_text(&mut input, &key, &sig, self.format)?;
+        let sig = if Path::new(&self.sig).is_file() {
+            get_content(&self.sig)?
+        } else {
+            self.sig.into_bytes()
+        };
+        let detached = DetachedSignature::parse(&sig)?;
+        let format = match &detached {
+            Some(detached) => {
+                if let Some(format) = self.format.filter(|f| *f != detached.algorithm) {
+                    return Err(anyhow::anyhow!(
+                        "signature is {}, not {}",
+                        detached.algorithm,
+                        format
+                    ));
+                }
+                detached.algorithm.clone()
+            }
+            None => self.format.unwrap_or(TextSignFormat::Blake3),
+        };
+        let key = read_key(&self.key, key_label(&format, false))?;
+        let r = match detached {
+            Some(detached) => {
+                let r = detached.verify(&mut input, &key, unix_time()?)?;
+                if let Some(comment) = detached.comment.as_ref().filter(|_| r) {
+                    eprintln!("comment : {}", comment);
+                }
+                r
+            }
+            None => verify_text(
+                &mut input,
+                &key,
+                &decode_armored(&sig, "SIGNATURE")?,
+                format,
+            )?,
+        };
         println!("{}", r);
         Ok(())
     }
@@ -126,22 +316,105 @@ pub struct TextSignArgs {
 
     #[arg(long, value_enum,default_value_t=TextSignFormat::Blake3)]
     pub format: TextSignFormat,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        group = "detached",
+        help = "PEM-style signature file with metadata"
+    )]
+    pub armor: bool,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        group = "detached",
+        help = "JSON signature file with metadata"
+    )]
+    pub json: bool,
+
+    #[arg(long,value_parser=parse_duration,requires="detached",help="Signature lifetime, e.g. 3600, 12h, 30d")]
+    pub expires_in: Option<u64>,
+
+    #[arg(long, requires = "detached")]
+    pub comment: Option<String>,
+
+    #[arg(short, long, help = "Output file, print to stdout if not set")]
+    pub output: Option<String>,
 }
 impl CmdExecutor for TextSignArgs {
     async fn execute(self) -> anyhow::Result<()> {
         let mut input = get_reader(&self.input)?;
-        let key = get_content(&self.key)?;
-        let sig = sign_text(&mut input, &key, self.format)?;
-        let encoded = BASE64_URL_SAFE_NO_PAD.encode(sig);
-        println!("{}", encoded);
+        let key = read_key(&self.key, key_label(&self.format, true))?;
+        let encoded = if self.armor || self.json {
+            let detached = DetachedSignature::sign(
+                &mut input,
+                self.format,
+                &key,
+                unix_time()?,
+                self.expires_in,
+                self.comment,
+            )?;
+            if self.json {
+                serde_json::to_string_pretty(&detached)?
+            } else {
+                detached.to_armor()?.to_string()
+            }
+        } else {
+            BASE64_URL_SAFE_NO_PAD.encode(sign_text(&mut input, &key, self.format.clone())?)
+        };
+        let mut output = get_writer(self.output.as_deref())?;
+        writeln!(output, "{}", encoded)?;
         Ok(())
     }
 }
 
-#[derive(Debug, Clone, ValueEnum)]
+fn parse_duration(s: &str) -> Result<u64, String> {
+    let (number, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let scale = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 604800,
+        _ => {
+            return Err(format!(
+                "invalid duration unit {:?}, use s, m, h, d or w",
+                unit
+            ))
+        }
+    };
+    let n = number.parse::<u64>().map_err(|e| e.to_string())?;
+    n.checked_mul(scale)
+        .ok_or_else(|| format!("duration {:?} is too long", s))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum KeyFormat {
+    Raw,
+    Pem,
+    Openssh,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TextSignFormat {
     Blake3,
     Ed25519,
+    Ed25519ph,
+}
+
+impl fmt::Display for TextSignFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextSignFormat::Blake3 => write!(f, "blake3"),
+            TextSignFormat::Ed25519 => write!(f, "ed25519"),
+            TextSignFormat::Ed25519ph => write!(f, "ed25519ph"),
+        }
+    }
 }
 
 impl FromStr for TextSignFormat {
@@ -151,6 +424,7 @@ impl FromStr for TextSignFormat {
         match s {
             "blake3" => Ok(TextSignFormat::Blake3),
             "ed25519" => Ok(TextSignFormat::Ed25519),
+            "ed25519ph" => Ok(TextSignFormat::Ed25519ph),
             _ => Err(anyhow::anyhow!("invalid format : {}", s)),
         }
     }
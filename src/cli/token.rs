@@ -0,0 +1,129 @@
+use crate::{
+    process::{
+        check_api_key, generate_api_key, generate_nanoid, generate_ulid, generate_uuid,
+        NANOID_ALPHABET,
+    },
+    CmdExecutor,
+};
+
+use clap::{Args, Subcommand, ValueEnum};
+use enum_dispatch::enum_dispatch;
+
+#[derive(Debug, Subcommand)]
+#[enum_dispatch(CmdExecutor)]
+pub enum TokenSubCommands {
+    #[command(name = "uuid", about = "generate a UUID v4 or v7")]
+    TokenUuid(TokenUuidArgs),
+
+    #[command(name = "ulid", about = "generate a ULID")]
+    TokenUlid(TokenUlidArgs),
+
+    #[command(name = "nanoid", about = "generate a NanoID")]
+    TokenNanoid(TokenNanoidArgs),
+
+    #[command(name = "key", about = "generate a prefixed API key with a checksum")]
+    TokenKey(TokenKeyArgs),
+
+    #[command(name = "check", about = "validate the checksum of an API key")]
+    TokenCheck(TokenCheckArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct TokenUuidArgs {
+    #[arg(long,value_enum,default_value_t=UuidVersion::V4)]
+    pub kind: UuidVersion,
+}
+
+impl CmdExecutor for TokenUuidArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        println!("{}", generate_uuid(self.kind)?);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct TokenUlidArgs {}
+
+impl CmdExecutor for TokenUlidArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        println!("{}", generate_ulid()?);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct TokenNanoidArgs {
+    #[arg(short, long, default_value_t = 21)]
+    pub len: usize,
+
+    #[arg(long, default_value = NANOID_ALPHABET)]
+    pub alphabet: String,
+}
+
+impl CmdExecutor for TokenNanoidArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        println!("{}", generate_nanoid(self.len, &self.alphabet)?);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct TokenKeyArgs {
+    #[arg(long, default_value = "rk_live")]
+    pub prefix: String,
+
+    #[arg(long, default_value_t = 24, help = "Random bytes before the checksum")]
+    pub bytes: usize,
+
+    #[arg(long,value_enum,default_value_t=TokenEncoding::Base58)]
+    pub encoding: TokenEncoding,
+
+    #[arg(long,value_enum,default_value_t=TokenChecksum::Crc32)]
+    pub checksum: TokenChecksum,
+}
+
+impl CmdExecutor for TokenKeyArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let key = generate_api_key(&self.prefix, self.bytes, self.encoding, self.checksum)?;
+        println!("{}", key);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct TokenCheckArgs {
+    pub token: String,
+}
+
+impl CmdExecutor for TokenCheckArgs {
+    async fn execute(self) -> anyhow::Result<()> {
+        let r = check_api_key(self.token.trim());
+        if let Some((prefix, encoding, checksum)) = &r {
+            eprintln!("prefix : {}", prefix);
+            eprintln!("encoding : {:?}", encoding);
+            eprintln!("checksum : {:?}", checksum);
+        }
+        println!("{}", r.is_some());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum UuidVersion {
+    V4,
+    V7,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TokenEncoding {
+    Hex,
+    Base32,
+    Base58,
+    Base64url,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TokenChecksum {
+    Crc32,
+    Blake3,
+}
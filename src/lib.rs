@@ -5,13 +5,27 @@ pub mod process;
 pub mod utils;
 
 use crate::cli::{
-    base64::{Base64DecodeArgs, Base64EncodeArgs, Base64SubCommands},
-    csv::CsvArgs,
-    genpass::GenPassArgs,
+    base64::{Base64DataUriArgs, Base64DecodeArgs, Base64EncodeArgs, Base64SubCommands},
+    codec::{DecodeArgs, EncodeArgs},
+    compress::{CompressArgs, CompressSubCommands, CompressTrainArgs, DecompressArgs},
+    csv::{CsvArgs, CsvSchemaArgs, CsvSubCommands},
+    genpass::{
+        GenPassArgs, GenPassBreachedArgs, GenPassCheckArgs, GenPassDeriveArgs, GenPassSubCommands,
+    },
     http::{HttpServeArgs, HttpSubCommands},
+    otp::{OtpCodeArgs, OtpSecretArgs, OtpSubCommands, OtpUriArgs, OtpVerifyArgs},
+    pipe::PipeArgs,
+    random::{
+        RandomBytesArgs, RandomDiceArgs, RandomFloatArgs, RandomIntArgs, RandomPickArgs,
+        RandomShuffleArgs, RandomSubCommands,
+    },
     text::{
-        KeyGenerateArgs, TextDecryptArgs, TextEncryptArgs, TextSignArgs, TextSubCommands,
-        TextVerifyArgs,
+        KeyDeriveArgs, KeyExportArgs, KeyGenerateArgs, TextDecryptArgs, TextEncryptArgs,
+        TextSignArgs, TextSubCommands, TextVerifyArgs,
+    },
+    token::{
+        TokenCheckArgs, TokenKeyArgs, TokenNanoidArgs, TokenSubCommands, TokenUlidArgs,
+        TokenUuidArgs,
     },
     SubCommands,
 };
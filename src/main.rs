@@ -7,6 +7,13 @@ async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
 
     let cli = Cli::parse();
+    if let Some(seed) = &cli.seed {
+        eprintln!(
+            "WARNING: --seed is set, every generated password, key and token is predictable."
+        );
+        eprintln!("WARNING: only use --seed for test fixtures, never for real secrets.");
+        rcli::utils::set_seed(seed)?;
+    }
     cli.command.execute().await?;
     Ok(())
 }
@@ -1,30 +1,143 @@
 use crate::cli::base64::Base64Format;
-use std::io::Read;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
 
-use anyhow::Ok;
-use base64::prelude::*;
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    prelude::*,
+    read::DecoderReader,
+    write::EncoderWriter,
+};
 
-pub fn base64_encode(input: &mut dyn Read, format: Base64Format) -> anyhow::Result<String> {
-    let mut content = Vec::new();
-    input.read_to_end(&mut content)?;
+const BUF_SIZE: usize = 64 * 1024;
 
-    let encoded = match format {
-        Base64Format::Stand => BASE64_STANDARD.encode(&content),
-        Base64Format::UrlSafe => BASE64_URL_SAFE.encode(&content),
+// accepts both padded and unpadded input, url-safe chars are mapped to standard first
+const AUTO: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+pub(crate) fn engine(format: Base64Format) -> &'static GeneralPurpose {
+    match format {
+        Base64Format::Stand => &BASE64_STANDARD,
+        Base64Format::UrlSafe => &BASE64_URL_SAFE,
+        Base64Format::StandNoPad => &BASE64_STANDARD_NO_PAD,
+        Base64Format::UrlSafeNoPad => &BASE64_URL_SAFE_NO_PAD,
+        Base64Format::Auto => &AUTO,
+    }
+}
+
+// drops whitespace so wrapped or newline-terminated input decodes
+struct SkipWhitespace<R> {
+    inner: BufReader<R>,
+    url_to_stand: bool,
+}
+
+impl<R: Read> Read for SkipWhitespace<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let chunk = self.inner.fill_buf()?;
+            if chunk.is_empty() {
+                return Ok(0);
+            }
+            let mut n = 0;
+            let mut consumed = 0;
+            for &b in chunk {
+                if n == buf.len() {
+                    break;
+                }
+                consumed += 1;
+                if b.is_ascii_whitespace() {
+                    continue;
+                }
+                buf[n] = match b {
+                    b'-' if self.url_to_stand => b'+',
+                    b'_' if self.url_to_stand => b'/',
+                    b => b,
+                };
+                n += 1;
+            }
+            self.inner.consume(consumed);
+            if n > 0 {
+                return Ok(n);
+            }
+        }
+    }
+}
+
+struct LineWrap<W: Write> {
+    inner: W,
+    width: usize,
+    column: usize,
+}
+
+impl<W: Write> Write for LineWrap<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        while !rest.is_empty() {
+            if self.column == self.width {
+                self.inner.write_all(b"\n")?;
+                self.column = 0;
+            }
+            let n = rest.len().min(self.width - self.column);
+            self.inner.write_all(&rest[..n])?;
+            self.column += n;
+            rest = &rest[n..];
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub fn base64_encode(
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+    format: Base64Format,
+    wrap: Option<usize>,
+) -> anyhow::Result<u64> {
+    if let Base64Format::Auto = format {
+        return Err(anyhow::anyhow!(
+            "auto format is only supported for decoding"
+        ));
+    }
+    let mut input = BufReader::with_capacity(BUF_SIZE, input);
+    let mut output = BufWriter::with_capacity(BUF_SIZE, output);
+    let output: &mut dyn Write = match wrap {
+        Some(0) => return Err(anyhow::anyhow!("wrap width must be greater than 0")),
+        Some(width) => &mut LineWrap {
+            inner: &mut output,
+            width,
+            column: 0,
+        },
+        None => &mut output,
     };
-    Ok(encoded)
+    let mut encoder = EncoderWriter::new(output, engine(format));
+    let n = io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.flush()?;
+    Ok(n)
 }
 
-pub fn base64_decode(input: &mut dyn Read, format: Base64Format) -> anyhow::Result<String> {
-    let mut content = String::new();
-    input.read_to_string(&mut content)?;
-    let content = content.trim();
-    let decoded = match format {
-        Base64Format::Stand => BASE64_STANDARD.decode(content)?,
-        Base64Format::UrlSafe => BASE64_URL_SAFE.decode(content)?,
+pub fn base64_reader<'a>(input: impl Read + 'a, format: Base64Format) -> impl Read + 'a {
+    let input = SkipWhitespace {
+        inner: BufReader::with_capacity(BUF_SIZE, input),
+        url_to_stand: matches!(format, Base64Format::Auto),
     };
+    DecoderReader::new(input, engine(format))
+}
 
-    Ok(String::from_utf8(decoded)?)
+pub fn base64_decode(
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+    format: Base64Format,
+) -> anyhow::Result<u64> {
+    let mut decoder = base64_reader(input, format);
+    let mut output = BufWriter::with_capacity(BUF_SIZE, output);
+    let n = io::copy(&mut decoder, &mut output)?;
+    output.flush()?;
+    Ok(n)
 }
 
 #[cfg(test)]
@@ -37,7 +150,12 @@ mod tests {
     fn test_base64_encode() -> anyhow::Result<()> {
         let file = "Cargo.toml";
         let mut input = get_reader(file)?;
-        assert!(base64_encode(&mut input, Base64Format::Stand).is_ok());
+        let mut output = Vec::new();
+        base64_encode(&mut input, &mut output, Base64Format::Stand, None)?;
+        assert_eq!(
+            BASE64_STANDARD.decode(&output)?,
+            std::fs::read("Cargo.toml")?
+        );
         Ok(())
     }
 
@@ -45,7 +163,51 @@ mod tests {
     fn test_base64_decode() -> anyhow::Result<()> {
         let file = "fixtures/passwordb64.txt";
         let mut input = get_reader(file)?;
-        assert!(base64_decode(&mut input, Base64Format::Stand).is_ok());
+        let mut output = Vec::new();
+        assert!(base64_decode(&mut input, &mut output, Base64Format::Stand).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_base64_roundtrip_large() -> anyhow::Result<()> {
+        let data = (0..BUF_SIZE * 3 + 7)
+            .map(|i| (i * 31 % 251) as u8)
+            .collect::<Vec<_>>();
+        for format in [Base64Format::Stand, Base64Format::UrlSafe] {
+            let mut encoded = Vec::new();
+            base64_encode(&mut data.as_slice(), &mut encoded, format, None)?;
+            assert_eq!(encoded, engine(format).encode(&data).into_bytes());
+
+            encoded.extend_from_slice(b"\r\n");
+            let mut decoded = Vec::new();
+            let n = base64_decode(&mut encoded.as_slice(), &mut decoded, format)?;
+            assert_eq!(n as usize, data.len());
+            assert_eq!(decoded, data);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_base64_variants() -> anyhow::Result<()> {
+        let data = b"\xfb\xff\xfe hello world, this needs more than one line of output!!!";
+        let mut wrapped = Vec::new();
+        base64_encode(&mut &data[..], &mut wrapped, Base64Format::Stand, Some(76))?;
+        let wrapped = String::from_utf8(wrapped)?;
+        assert!(wrapped.lines().all(|line| line.len() <= 76));
+        assert_eq!(wrapped.lines().count(), 2);
+
+        let sig = BASE64_URL_SAFE_NO_PAD.encode(data);
+        for (input, format) in [
+            (wrapped.replace('\n', "\r\n "), Base64Format::Stand),
+            (sig.clone(), Base64Format::UrlSafeNoPad),
+            (sig, Base64Format::Auto),
+            (wrapped, Base64Format::Auto),
+        ] {
+            let mut decoded = Vec::new();
+            base64_decode(&mut input.as_bytes(), &mut decoded, format)?;
+            assert_eq!(decoded, data);
+        }
+        assert!(base64_encode(&mut &data[..], &mut Vec::new(), Base64Format::Auto, None).is_err());
         Ok(())
     }
 }
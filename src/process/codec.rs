@@ -0,0 +1,448 @@
+use anyhow::Result;
+use data_encoding::{Specification, BASE32, BASE32_NOPAD, HEXLOWER, HEXLOWER_PERMISSIVE};
+use percent_encoding::{percent_decode_str, percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::fmt::Write;
+
+use crate::cli::base64::Base64Format;
+use crate::cli::codec::CodecKind;
+use crate::process::{base64_decode, base64_encode, token::CROCKFORD};
+
+pub trait Codec {
+    fn encode(&self, data: &[u8]) -> Result<String>;
+    fn decode(&self, text: &str) -> Result<Vec<u8>>;
+}
+
+pub fn get_codec(kind: CodecKind) -> Box<dyn Codec> {
+    match kind {
+        CodecKind::Hex => Box::new(Hex),
+        CodecKind::Xxd => Box::new(Xxd),
+        CodecKind::Base32 => Box::new(Base32),
+        CodecKind::Base32Crockford => Box::new(Base32Crockford),
+        CodecKind::Base58 => Box::new(Base58 { check: false }),
+        CodecKind::Base58check => Box::new(Base58 { check: true }),
+        CodecKind::Base64 => Box::new(Base64(Base64Format::Stand)),
+        CodecKind::Base64url => Box::new(Base64(Base64Format::UrlSafeNoPad)),
+        CodecKind::Ascii85 => Box::new(Ascii85),
+        CodecKind::Z85 => Box::new(Z85),
+        CodecKind::Percent => Box::new(Percent),
+        CodecKind::Html => Box::new(Html),
+    }
+}
+
+fn strip_whitespace(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+pub struct Hex;
+
+impl Codec for Hex {
+    fn encode(&self, data: &[u8]) -> Result<String> {
+        Ok(HEXLOWER.encode(data))
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>> {
+        Ok(HEXLOWER_PERMISSIVE.decode(strip_whitespace(text).as_bytes())?)
+    }
+}
+
+pub struct Xxd;
+
+const XXD_WIDTH: usize = 16;
+const XXD_HEX_COLUMNS: usize = XXD_WIDTH / 2 * 5;
+
+impl Codec for Xxd {
+    fn encode(&self, data: &[u8]) -> Result<String> {
+        let mut dump = String::new();
+        for (i, line) in data.chunks(XXD_WIDTH).enumerate() {
+            let _ = write!(dump, "{:08x}: ", i * XXD_WIDTH);
+            for j in 0..XXD_WIDTH {
+                match line.get(j) {
+                    Some(b) => {
+                        let _ = write!(dump, "{:02x}", b);
+                    }
+                    None => dump.push_str("  "),
+                }
+                if j % 2 == 1 {
+                    dump.push(' ');
+                }
+            }
+            dump.push(' ');
+            dump.extend(line.iter().map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            }));
+            dump.push('\n');
+        }
+        Ok(dump.trim_end().to_string())
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let (_, rest) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow::anyhow!("invalid xxd line {:?}", line))?;
+            let rest = rest.strip_prefix(' ').unwrap_or(rest);
+            let hex = rest.get(..XXD_HEX_COLUMNS).unwrap_or(rest);
+            data.extend(Hex.decode(hex)?);
+        }
+        Ok(data)
+    }
+}
+
+pub struct Base32;
+
+impl Codec for Base32 {
+    fn encode(&self, data: &[u8]) -> Result<String> {
+        Ok(BASE32.encode(data))
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>> {
+        let text = strip_whitespace(text).to_uppercase();
+        Ok(BASE32
+            .decode(text.as_bytes())
+            .or_else(|_| BASE32_NOPAD.decode(text.as_bytes()))?)
+    }
+}
+
+pub struct Base32Crockford;
+
+impl Base32Crockford {
+    fn encoding() -> Result<data_encoding::Encoding> {
+        let mut spec = Specification::new();
+        spec.symbols.push_str(std::str::from_utf8(CROCKFORD)?);
+        spec.translate.from.push_str("abcdefghjkmnpqrstvwxyzIiLlOo");
+        spec.translate.to.push_str("ABCDEFGHJKMNPQRSTVWXYZ111100");
+        Ok(spec.encoding()?)
+    }
+}
+
+impl Codec for Base32Crockford {
+    fn encode(&self, data: &[u8]) -> Result<String> {
+        Ok(Self::encoding()?.encode(data))
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>> {
+        let text = strip_whitespace(text).replace('-', "");
+        Ok(Self::encoding()?.decode(text.as_bytes())?)
+    }
+}
+
+pub struct Base58 {
+    pub check: bool,
+}
+
+impl Codec for Base58 {
+    fn encode(&self, data: &[u8]) -> Result<String> {
+        let encoder = bs58::encode(data);
+        Ok(if self.check {
+            encoder.with_check().into_string()
+        } else {
+            encoder.into_string()
+        })
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>> {
+        let text = strip_whitespace(text);
+        let decoder = bs58::decode(&text);
+        Ok(if self.check {
+            decoder.with_check(None).into_vec()?
+        } else {
+            decoder.into_vec()?
+        })
+    }
+}
+
+pub struct Base64(pub Base64Format);
+
+impl Codec for Base64 {
+    fn encode(&self, data: &[u8]) -> Result<String> {
+        let mut encoded = Vec::new();
+        base64_encode(&mut &data[..], &mut encoded, self.0, None)?;
+        Ok(String::from_utf8(encoded)?)
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>> {
+        let mut decoded = Vec::new();
+        base64_decode(&mut text.as_bytes(), &mut decoded, Base64Format::Auto)?;
+        Ok(decoded)
+    }
+}
+
+const Z85_ALPHABET: &[u8] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+fn base85_digits(value: u32, digit: impl Fn(u8) -> u8) -> [u8; 5] {
+    let mut digits = [0u8; 5];
+    let mut value = value;
+    for d in digits.iter_mut().rev() {
+        *d = digit((value % 85) as u8);
+        value /= 85;
+    }
+    digits
+}
+
+fn base85_value(group: &[u8], index: impl Fn(u8) -> Option<u8>) -> Result<u32> {
+    let value = group.iter().try_fold(0u64, |acc, &c| {
+        index(c)
+            .map(|i| acc * 85 + i as u64)
+            .ok_or_else(|| anyhow::anyhow!("invalid base85 character {:?}", c as char))
+    })?;
+    u32::try_from(value).map_err(|_| anyhow::anyhow!("base85 group out of range"))
+}
+
+pub struct Ascii85;
+
+impl Codec for Ascii85 {
+    fn encode(&self, data: &[u8]) -> Result<String> {
+        let mut encoded = Vec::new();
+        for chunk in data.chunks(4) {
+            let mut group = [0u8; 4];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let value = u32::from_be_bytes(group);
+            if chunk.len() == 4 && value == 0 {
+                encoded.push(b'z');
+            } else {
+                encoded.extend(&base85_digits(value, |d| d + b'!')[..chunk.len() + 1]);
+            }
+        }
+        Ok(String::from_utf8(encoded)?)
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>> {
+        let text = strip_whitespace(text);
+        let text = text.strip_prefix("<~").unwrap_or(&text);
+        let text = text.strip_suffix("~>").unwrap_or(text);
+        let mut data = Vec::new();
+        let mut group = Vec::with_capacity(5);
+        for c in text.bytes() {
+            if c == b'z' && group.is_empty() {
+                data.extend([0u8; 4]);
+                continue;
+            }
+            group.push(c);
+            if group.len() == 5 {
+                let value = base85_value(&group, |c| (b'!'..=b'u').contains(&c).then(|| c - b'!'))?;
+                data.extend(value.to_be_bytes());
+                group.clear();
+            }
+        }
+        match group.len() {
+            0 => {}
+            1 => return Err(anyhow::anyhow!("invalid ascii85 length")),
+            n => {
+                group.resize(5, b'u');
+                let value = base85_value(&group, |c| (b'!'..=b'u').contains(&c).then(|| c - b'!'))?;
+                data.extend(&value.to_be_bytes()[..n - 1]);
+            }
+        }
+        Ok(data)
+    }
+}
+
+pub struct Z85;
+
+impl Codec for Z85 {
+    fn encode(&self, data: &[u8]) -> Result<String> {
+        if !data.len().is_multiple_of(4) {
+            return Err(anyhow::anyhow!("z85 input length must be a multiple of 4"));
+        }
+        let mut encoded = Vec::with_capacity(data.len() / 4 * 5);
+        for chunk in data.chunks(4) {
+            let value = u32::from_be_bytes(chunk.try_into()?);
+            encoded.extend(base85_digits(value, |d| Z85_ALPHABET[d as usize]));
+        }
+        Ok(String::from_utf8(encoded)?)
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>> {
+        let text = strip_whitespace(text);
+        if !text.len().is_multiple_of(5) {
+            return Err(anyhow::anyhow!("z85 input length must be a multiple of 5"));
+        }
+        let mut data = Vec::with_capacity(text.len() / 5 * 4);
+        for group in text.as_bytes().chunks(5) {
+            let value = base85_value(group, |c| {
+                Z85_ALPHABET.iter().position(|&a| a == c).map(|i| i as u8)
+            })?;
+            data.extend(value.to_be_bytes());
+        }
+        Ok(data)
+    }
+}
+
+// same set as JavaScript encodeURIComponent
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'!')
+    .remove(b'~')
+    .remove(b'*')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')');
+
+pub struct Percent;
+
+impl Codec for Percent {
+    fn encode(&self, data: &[u8]) -> Result<String> {
+        Ok(percent_encode(data, COMPONENT).to_string())
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>> {
+        Ok(percent_decode_str(text).collect())
+    }
+}
+
+pub struct Html;
+
+const HTML_ENTITIES: [(&str, char); 6] = [
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{a0}'),
+];
+
+impl Codec for Html {
+    fn encode(&self, data: &[u8]) -> Result<String> {
+        let text = std::str::from_utf8(data)
+            .map_err(|_| anyhow::anyhow!("html entities need UTF-8 input"))?;
+        let mut encoded = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '&' => encoded.push_str("&amp;"),
+                '<' => encoded.push_str("&lt;"),
+                '>' => encoded.push_str("&gt;"),
+                '"' => encoded.push_str("&quot;"),
+                '\'' => encoded.push_str("&#39;"),
+                c => encoded.push(c),
+            }
+        }
+        Ok(encoded)
+    }
+
+    fn decode(&self, text: &str) -> Result<Vec<u8>> {
+        let mut decoded = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('&') {
+            decoded.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let entity = rest[1..]
+                .find(';')
+                .filter(|&end| end <= 32)
+                .and_then(|end| Some((end, html_entity(&rest[1..end + 1])?)));
+            match entity {
+                Some((end, c)) => {
+                    decoded.push(c);
+                    rest = &rest[end + 2..];
+                }
+                None => {
+                    decoded.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        decoded.push_str(rest);
+        Ok(decoded.into_bytes())
+    }
+}
+
+fn html_entity(name: &str) -> Option<char> {
+    let code = match name.strip_prefix('#') {
+        Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok()?,
+        Some(dec) => dec.parse().ok()?,
+        None => {
+            return HTML_ENTITIES
+                .iter()
+                .find(|(entity, _)| *entity == name)
+                .map(|(_, c)| *c)
+        }
+    };
+    char::from_u32(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KINDS: [CodecKind; 12] = [
+        CodecKind::Hex,
+        CodecKind::Xxd,
+        CodecKind::Base32,
+        CodecKind::Base32Crockford,
+        CodecKind::Base58,
+        CodecKind::Base58check,
+        CodecKind::Base64,
+        CodecKind::Base64url,
+        CodecKind::Ascii85,
+        CodecKind::Z85,
+        CodecKind::Percent,
+        CodecKind::Html,
+    ];
+
+    #[test]
+    fn test_codec_roundtrip() -> Result<()> {
+        let binary = (0..=255u8).chain([0, 0, 0, 0, 7]).collect::<Vec<_>>();
+        let text = "<a href=\"x?q=1&r='2'\">héllo 😀</a>".as_bytes();
+        for kind in KINDS {
+            let data = match kind {
+                CodecKind::Html => text,
+                CodecKind::Z85 => &binary[..256],
+                _ => &binary[..],
+            };
+            let codec = get_codec(kind);
+            let encoded = codec.encode(data)?;
+            assert_eq!(codec.decode(&encoded)?, data, "{:?}", kind);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_vectors() -> Result<()> {
+        let hello = b"Hello World!";
+        assert_eq!(
+            get_codec(CodecKind::Base58).encode(hello)?,
+            "2NEpo7TZRRrLZSi2U"
+        );
+        assert_eq!(get_codec(CodecKind::Base32).encode(b"foob")?, "MZXW6YQ=");
+        assert_eq!(
+            get_codec(CodecKind::Base32Crockford).decode("csqpyrk1-e8")?,
+            b"foobar"
+        );
+        assert_eq!(get_codec(CodecKind::Ascii85).encode(b"Man ")?, "9jqo^");
+        assert_eq!(get_codec(CodecKind::Ascii85).decode("<~9jqo^~>")?, b"Man ");
+        assert_eq!(
+            get_codec(CodecKind::Z85).encode(&[0x86, 0x4f, 0xd2, 0x6f, 0xb5, 0x59, 0xf7, 0x5b])?,
+            "HelloWorld"
+        );
+        assert_eq!(
+            get_codec(CodecKind::Percent).encode("a b/é".as_bytes())?,
+            "a%20b%2F%C3%A9"
+        );
+        assert_eq!(
+            get_codec(CodecKind::Html).decode("&lt;p&gt; &#x41;&#66; &bogus; &amp")?,
+            b"<p> AB &bogus; &amp"
+        );
+        assert!(get_codec(CodecKind::Base58check)
+            .decode("2NEpo7TZRRrLZSi2U")
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_xxd_dump() -> Result<()> {
+        let dump = get_codec(CodecKind::Xxd).encode(b"Hello, world!\nsecond line\n")?;
+        assert_eq!(
+            dump,
+            "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a 7365  Hello, world!.se\n\
+             00000010: 636f 6e64 206c 696e 650a                 cond line."
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,402 @@
+use anyhow::Result;
+use flate2::{
+    read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder},
+    write::{DeflateEncoder, GzEncoder, ZlibEncoder},
+    Compression,
+};
+use std::{
+    fmt,
+    io::{self, BufReader, BufWriter, Cursor, Read, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+use xz2::{read::XzDecoder, write::XzEncoder};
+
+use crate::cli::compress::CompressFormat;
+
+pub(crate) const BUF_SIZE: usize = 64 * 1024;
+const BROTLI_LGWIN: u32 = 22;
+const MAX_EMPTY_WRITES: usize = 16;
+
+const MAGIC: [(&[u8], CompressFormat); 7] = [
+    (b"\x1f\x8b", CompressFormat::Gzip),
+    (b"\x28\xb5\x2f\xfd", CompressFormat::Zstd),
+    (b"\xfd7zXZ\x00", CompressFormat::Xz),
+    // zlib headers for the default window and every compression level
+    (b"\x78\x01", CompressFormat::Zlib),
+    (b"\x78\x5e", CompressFormat::Zlib),
+    (b"\x78\x9c", CompressFormat::Zlib),
+    (b"\x78\xda", CompressFormat::Zlib),
+];
+
+// a writer that has to write a trailer before the stream is complete
+pub trait Finish: Write {
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+struct Stage<W> {
+    writer: W,
+    finish: fn(W) -> io::Result<()>,
+}
+
+impl<W: Write> Write for Stage<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // base64's EncoderWriter returns Ok(0) after draining its pending output,
+        // a writer that keeps doing so is stuck rather than draining
+        for _ in 0..MAX_EMPTY_WRITES {
+            match self.writer.write(buf)? {
+                0 if !buf.is_empty() => continue,
+                n => return Ok(n),
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::WriteZero,
+            "stage accepted no input",
+        ))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> Finish for Stage<W> {
+    fn finish(self: Box<Self>) -> io::Result<()> {
+        (self.finish)(self.writer)
+    }
+}
+
+pub(crate) fn stage<'a, W: Write + 'a>(
+    writer: W,
+    finish: fn(W) -> io::Result<()>,
+) -> Box<dyn Finish + 'a> {
+    Box::new(Stage { writer, finish })
+}
+
+impl CompressFormat {
+    fn levels(&self) -> (i32, i32, i32) {
+        match self {
+            CompressFormat::Gzip | CompressFormat::Zlib | CompressFormat::Deflate => (0, 9, 6),
+            CompressFormat::Zstd => {
+                let range = zstd::compression_level_range();
+                (
+                    *range.start(),
+                    *range.end(),
+                    zstd::DEFAULT_COMPRESSION_LEVEL,
+                )
+            }
+            CompressFormat::Brotli => (0, 11, 9),
+            CompressFormat::Xz => (0, 9, 6),
+        }
+    }
+
+    fn level(&self, level: Option<i32>) -> Result<i32> {
+        let (min, max, default) = self.levels();
+        match level {
+            Some(level) if !(min..=max).contains(&level) => Err(anyhow::anyhow!(
+                "{} level must be between {} and {}",
+                self,
+                min,
+                max
+            )),
+            Some(level) => Ok(level),
+            None => Ok(default),
+        }
+    }
+
+    // wraps `output` so that everything written to it is compressed
+    pub fn encoder<'a>(
+        &self,
+        output: Box<dyn Finish + 'a>,
+        level: Option<i32>,
+        dict: Option<&[u8]>,
+    ) -> Result<Box<dyn Finish + 'a>> {
+        if dict.is_some() && *self != CompressFormat::Zstd {
+            return Err(anyhow::anyhow!("dictionaries are only supported for zstd"));
+        }
+        let level = self.level(level)?;
+        Ok(match self {
+            CompressFormat::Gzip => stage(
+                GzEncoder::new(output, Compression::new(level as u32)),
+                |w| w.finish()?.finish(),
+            ),
+            CompressFormat::Zlib => stage(
+                ZlibEncoder::new(output, Compression::new(level as u32)),
+                |w| w.finish()?.finish(),
+            ),
+            CompressFormat::Deflate => stage(
+                DeflateEncoder::new(output, Compression::new(level as u32)),
+                |w| w.finish()?.finish(),
+            ),
+            CompressFormat::Zstd => {
+                let encoder = match dict {
+                    Some(dict) => zstd::Encoder::with_dictionary(output, level, dict)?,
+                    None => zstd::Encoder::new(output, level)?,
+                };
+                stage(encoder, |w| w.finish()?.finish())
+            }
+            // into_inner finishes the stream but swallows errors, flush first to surface them
+            CompressFormat::Brotli => stage(
+                brotli::CompressorWriter::new(output, BUF_SIZE, level as u32, BROTLI_LGWIN),
+                |mut w| {
+                    w.flush()?;
+                    w.into_inner().finish()
+                },
+            ),
+            CompressFormat::Xz => stage(XzEncoder::new(output, level as u32), |w| {
+                w.finish()?.finish()
+            }),
+        })
+    }
+
+    pub fn decoder<'a>(
+        &self,
+        input: impl Read + 'a,
+        dict: Option<&[u8]>,
+    ) -> Result<Box<dyn Read + 'a>> {
+        Ok(match (self, dict) {
+            (CompressFormat::Zstd, Some(dict)) => Box::new(zstd::Decoder::with_dictionary(
+                BufReader::with_capacity(BUF_SIZE, input),
+                dict,
+            )?),
+            (_, Some(_)) => {
+                return Err(anyhow::anyhow!("dictionaries are only supported for zstd"))
+            }
+            (CompressFormat::Gzip, None) => Box::new(MultiGzDecoder::new(input)),
+            (CompressFormat::Zlib, None) => Box::new(ZlibDecoder::new(input)),
+            (CompressFormat::Deflate, None) => Box::new(DeflateDecoder::new(input)),
+            (CompressFormat::Zstd, None) => Box::new(zstd::Decoder::new(input)?),
+            (CompressFormat::Brotli, None) => Box::new(brotli::Decompressor::new(input, BUF_SIZE)),
+            (CompressFormat::Xz, None) => Box::new(XzDecoder::new_multi_decoder(input)),
+        })
+    }
+}
+
+impl fmt::Display for CompressFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CompressFormat::Gzip => "gzip",
+            CompressFormat::Zlib => "zlib",
+            CompressFormat::Deflate => "deflate",
+            CompressFormat::Zstd => "zstd",
+            CompressFormat::Brotli => "brotli",
+            CompressFormat::Xz => "xz",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub fn detect_format(header: &[u8], path: Option<&str>) -> Option<CompressFormat> {
+    let magic = MAGIC
+        .iter()
+        .find(|(magic, _)| header.starts_with(magic))
+        .map(|(_, format)| *format);
+    // brotli has no magic bytes, only the extension can tell
+    let brotli = path
+        .and_then(|path| Path::new(path).extension())
+        .is_some_and(|ext| ext == "br");
+    magic.or(brotli.then_some(CompressFormat::Brotli))
+}
+
+struct Counter<T> {
+    inner: T,
+    count: u64,
+}
+
+impl<T> Counter<T> {
+    fn new(inner: T) -> Self {
+        Self { inner, count: 0 }
+    }
+}
+
+impl<R: Read> Read for Counter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+impl<W: Write> Write for Counter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[derive(Debug)]
+pub struct CompressStats {
+    pub format: CompressFormat,
+    pub compressed: u64,
+    pub uncompressed: u64,
+    pub elapsed: Duration,
+}
+
+impl CompressStats {
+    pub fn ratio(&self) -> f64 {
+        if self.uncompressed == 0 {
+            return 0.0;
+        }
+        self.compressed as f64 / self.uncompressed as f64
+    }
+
+    // MB/s of uncompressed data in both directions
+    pub fn throughput(&self) -> f64 {
+        self.uncompressed as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON) / 1e6
+    }
+}
+
+impl fmt::Display for CompressStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "format : {}", self.format)?;
+        writeln!(f, "uncompressed : {} bytes", self.uncompressed)?;
+        writeln!(
+            f,
+            "compressed : {} bytes ({:.1}%)",
+            self.compressed,
+            self.ratio() * 100.0
+        )?;
+        write!(f, "throughput : {:.1} MB/s", self.throughput())
+    }
+}
+
+pub fn compress(
+    input: impl Read,
+    output: impl Write,
+    format: CompressFormat,
+    level: Option<i32>,
+    dict: Option<&[u8]>,
+) -> Result<CompressStats> {
+    let start = Instant::now();
+    let mut input = Counter::new(BufReader::with_capacity(BUF_SIZE, input));
+    let mut output = Counter::new(BufWriter::with_capacity(BUF_SIZE, output));
+    let mut encoder = format.encoder(stage(&mut output, |w| w.flush()), level, dict)?;
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    Ok(CompressStats {
+        format,
+        compressed: output.count,
+        uncompressed: input.count,
+        elapsed: start.elapsed(),
+    })
+}
+
+pub fn decompress(
+    mut input: impl Read,
+    output: impl Write,
+    format: Option<CompressFormat>,
+    path: Option<&str>,
+    dict: Option<&[u8]>,
+) -> Result<CompressStats> {
+    let start = Instant::now();
+    let mut header = Vec::new();
+    input.by_ref().take(6).read_to_end(&mut header)?;
+    let format = format
+        .or_else(|| detect_format(&header, path))
+        .ok_or_else(|| anyhow::anyhow!("cannot detect the compression format, use --format"))?;
+    let mut counted = Counter::new(Cursor::new(header).chain(input));
+    let mut output = Counter::new(BufWriter::with_capacity(BUF_SIZE, output));
+    let mut decoder = format.decoder(&mut counted, dict)?;
+    io::copy(&mut decoder, &mut output)?;
+    output.flush()?;
+    drop(decoder);
+    Ok(CompressStats {
+        format,
+        compressed: counted.count,
+        uncompressed: output.count,
+        elapsed: start.elapsed(),
+    })
+}
+
+pub fn train_dictionary(samples: &[String], max_size: usize) -> Result<Vec<u8>> {
+    Ok(zstd::dict::from_files(samples, max_size)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMATS: [CompressFormat; 6] = [
+        CompressFormat::Gzip,
+        CompressFormat::Zlib,
+        CompressFormat::Deflate,
+        CompressFormat::Zstd,
+        CompressFormat::Brotli,
+        CompressFormat::Xz,
+    ];
+
+    #[test]
+    fn test_compress_roundtrip() -> Result<()> {
+        let data = std::fs::read("assets/juventus.csv")?;
+        for format in FORMATS {
+            let mut compressed = Vec::new();
+            let stats = compress(&data[..], &mut compressed, format, None, None)?;
+            assert_eq!(stats.uncompressed, data.len() as u64);
+            assert_eq!(stats.compressed, compressed.len() as u64);
+            assert!(stats.ratio() < 1.0);
+
+            let path = matches!(format, CompressFormat::Brotli).then_some("data.csv.br");
+            // raw deflate has neither magic bytes nor a common extension
+            let hint = matches!(format, CompressFormat::Deflate).then_some(format);
+            let mut decompressed = Vec::new();
+            let stats = decompress(&compressed[..], &mut decompressed, hint, path, None)?;
+            assert_eq!(stats.format, format);
+            assert_eq!(decompressed, data);
+        }
+        assert!(compress(&data[..], io::sink(), CompressFormat::Gzip, Some(10), None).is_err());
+        let mut fast = Vec::new();
+        compress(&data[..], &mut fast, CompressFormat::Zstd, Some(-5), None)?;
+        let mut decompressed = Vec::new();
+        decompress(&fast[..], &mut decompressed, None, None, None)?;
+        assert_eq!(decompressed, data);
+        assert!(decompress(&b"plain"[..], io::sink(), None, None, None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zstd_dictionary() -> Result<()> {
+        let samples = (0..200)
+            .map(|i| {
+                format!(
+                    "{{\"id\":{},\"name\":\"user{}\",\"active\":true}}",
+                    i,
+                    i * 7
+                )
+            })
+            .collect::<Vec<_>>();
+        let dict = zstd::dict::from_samples(&samples, 1024)?;
+        let mut compressed = Vec::new();
+        compress(
+            samples[3].as_bytes(),
+            &mut compressed,
+            CompressFormat::Zstd,
+            None,
+            Some(&dict),
+        )?;
+        let mut decompressed = Vec::new();
+        decompress(&compressed[..], &mut decompressed, None, None, Some(&dict))?;
+        assert_eq!(decompressed, samples[3].as_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn test_stage_stuck_writer() {
+        struct Stuck;
+        impl Write for Stuck {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Ok(0)
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut stage = stage(Stuck, |_| Ok(()));
+        let err = stage.write(b"data").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+}
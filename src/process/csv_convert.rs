@@ -3,6 +3,7 @@ use std::fs;
 
 use crate::cli::csv::OutputFormat;
 
+#[allow(dead_code)]
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 struct Player {
@@ -0,0 +1,368 @@
+use serde_json::{json, Map, Value};
+use std::{collections::HashSet, fmt::Write, fs, io::Read};
+
+use crate::cli::csv::SchemaFormat;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Empty,
+    Boolean,
+    Integer { min: i64, max: i64 },
+    Float,
+    String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    pub ty: ColumnType,
+    pub nullable: bool,
+}
+
+impl ColumnType {
+    fn of(value: &str) -> Self {
+        if value.is_empty() {
+            ColumnType::Empty
+        } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+            ColumnType::Boolean
+        } else if let Ok(n) = value.parse::<i64>() {
+            ColumnType::Integer { min: n, max: n }
+        } else if value.parse::<f64>().is_ok() && value.bytes().any(|b| b.is_ascii_digit()) {
+            ColumnType::Float
+        } else {
+            ColumnType::String
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        use ColumnType::*;
+        match (self, other) {
+            (Empty, t) | (t, Empty) => t,
+            (Integer { min: a, max: b }, Integer { min: c, max: d }) => Integer {
+                min: a.min(c),
+                max: b.max(d),
+            },
+            (Integer { .. }, Float) | (Float, Integer { .. }) => Float,
+            (a, b) if a == b => a,
+            _ => String,
+        }
+    }
+
+    fn rust_type(&self) -> &'static str {
+        match *self {
+            ColumnType::Empty | ColumnType::String => "String",
+            ColumnType::Boolean => "bool",
+            ColumnType::Float => "f64",
+            ColumnType::Integer { min, max } if min >= 0 => match max {
+                m if m <= u8::MAX as i64 => "u8",
+                m if m <= u16::MAX as i64 => "u16",
+                m if m <= u32::MAX as i64 => "u32",
+                _ => "u64",
+            },
+            ColumnType::Integer { min, max } => {
+                if min >= i8::MIN as i64 && max <= i8::MAX as i64 {
+                    "i8"
+                } else if min >= i16::MIN as i64 && max <= i16::MAX as i64 {
+                    "i16"
+                } else if min >= i32::MIN as i64 && max <= i32::MAX as i64 {
+                    "i32"
+                } else {
+                    "i64"
+                }
+            }
+        }
+    }
+
+    fn json_type(&self) -> &'static str {
+        match self {
+            ColumnType::Empty | ColumnType::String => "string",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Integer { .. } => "integer",
+            ColumnType::Float => "number",
+        }
+    }
+}
+
+pub fn infer_schema(input: impl Read, delimiter: u8, sample: usize) -> anyhow::Result<Vec<Column>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_reader(input);
+    let mut columns = reader
+        .headers()?
+        .iter()
+        .map(|name| Column {
+            name: name.to_string(),
+            ty: ColumnType::Empty,
+            nullable: false,
+        })
+        .collect::<Vec<_>>();
+
+    for (i, record) in reader.records().enumerate() {
+        if sample > 0 && i >= sample {
+            break;
+        }
+        let record = record?;
+        for (column, value) in columns.iter_mut().zip(record.iter()) {
+            let ty = ColumnType::of(value.trim());
+            column.nullable |= ty == ColumnType::Empty;
+            column.ty = column.ty.merge(ty);
+        }
+    }
+    Ok(columns)
+}
+
+pub fn to_json_schema(name: &str, columns: &[Column]) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for column in columns {
+        let ty = if column.nullable {
+            json!([column.ty.json_type(), "null"])
+        } else {
+            required.push(column.name.clone());
+            json!(column.ty.json_type())
+        };
+        properties.insert(column.name.clone(), json!({ "type": ty }));
+    }
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": name,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+type RenameRule = (&'static str, fn(&str) -> String);
+
+const RENAME_RULES: [RenameRule; 5] = [
+    ("PascalCase", |s| s.split('_').map(capitalize).collect()),
+    ("camelCase", |s| {
+        let pascal: String = s.split('_').map(capitalize).collect();
+        let mut chars = pascal.chars();
+        chars
+            .next()
+            .map(|c| c.to_lowercase().chain(chars).collect())
+            .unwrap_or_default()
+    }),
+    ("SCREAMING_SNAKE_CASE", |s| s.to_uppercase()),
+    ("kebab-case", |s| s.replace('_', "-")),
+    ("UPPERCASE", |s| s.to_uppercase().replace('_', "")),
+];
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+fn field_name(header: &str) -> String {
+    let mut name = String::new();
+    let mut prev_lower = false;
+    for c in header.chars() {
+        if c.is_alphanumeric() {
+            if c.is_uppercase() && prev_lower {
+                name.push('_');
+            }
+            name.extend(c.to_lowercase());
+            prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        } else {
+            if !name.is_empty() && !name.ends_with('_') {
+                name.push('_');
+            }
+            prev_lower = false;
+        }
+    }
+    let name = name.trim_end_matches('_').to_string();
+    match name.chars().next() {
+        None => "field".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("field_{}", name),
+        _ if matches!(name.as_str(), "self" | "super" | "crate") => format!("{}_", name),
+        _ if is_keyword(&name) => format!("r#{}", name),
+        _ => name,
+    }
+}
+
+fn is_keyword(name: &str) -> bool {
+    matches!(
+        name,
+        "as" | "async"
+            | "await"
+            | "break"
+            | "const"
+            | "continue"
+            | "dyn"
+            | "else"
+            | "enum"
+            | "extern"
+            | "false"
+            | "fn"
+            | "for"
+            | "if"
+            | "impl"
+            | "in"
+            | "let"
+            | "loop"
+            | "match"
+            | "mod"
+            | "move"
+            | "mut"
+            | "pub"
+            | "ref"
+            | "return"
+            | "static"
+            | "struct"
+            | "trait"
+            | "true"
+            | "type"
+            | "unsafe"
+            | "use"
+            | "where"
+            | "while"
+    )
+}
+
+pub fn to_rust_struct(name: &str, columns: &[Column]) -> String {
+    // headers like "Date of Birth" and "date_of_birth" normalize to the same field
+    let mut seen = HashSet::new();
+    let fields = columns
+        .iter()
+        .map(|c| {
+            let base = field_name(&c.name);
+            let mut field = base.clone();
+            let mut n = 2;
+            while !seen.insert(field.clone()) {
+                field = format!("{}_{}", base.trim_start_matches("r#"), n);
+                n += 1;
+            }
+            (field, c)
+        })
+        .collect::<Vec<_>>();
+
+    // pick the serde rename_all rule that covers most headers, per-field renames for the rest
+    let snake_matches = fields.iter().filter(|(f, c)| *f == c.name).count();
+    let rule = RENAME_RULES
+        .iter()
+        .map(|(rule, convert)| {
+            let matches = fields
+                .iter()
+                .filter(|(f, c)| convert(f.trim_start_matches("r#")) == c.name)
+                .count();
+            (rule, convert, matches)
+        })
+        .filter(|(_, _, matches)| *matches > snake_matches)
+        .max_by_key(|(_, _, matches)| *matches);
+
+    let mut code = String::from("use serde::{Deserialize, Serialize};\n\n");
+    code.push_str("#[derive(Debug, Deserialize, Serialize)]\n");
+    if let Some((rule, _, _)) = rule {
+        let _ = writeln!(code, "#[serde(rename_all = \"{}\")]", rule);
+    }
+    let _ = writeln!(code, "pub struct {} {{", name);
+    for (field, column) in &fields {
+        let renamed = match rule {
+            Some((_, convert, _)) => convert(field.trim_start_matches("r#")),
+            None => field.trim_start_matches("r#").to_string(),
+        };
+        if renamed != column.name {
+            let _ = writeln!(code, "    #[serde(rename = {:?})]", column.name);
+        }
+        let ty = column.ty.rust_type();
+        if column.nullable {
+            let _ = writeln!(code, "    pub {}: Option<{}>,", field, ty);
+        } else {
+            let _ = writeln!(code, "    pub {}: {},", field, ty);
+        }
+    }
+    code.push_str("}\n");
+    code
+}
+
+pub fn process_csv_schema(
+    input: &str,
+    output: Option<&str>,
+    format: SchemaFormat,
+    delimiter: u8,
+    sample: usize,
+    name: &str,
+) -> anyhow::Result<()> {
+    let reader = crate::utils::get_reader(input)?;
+    let columns = infer_schema(reader, delimiter, sample)?;
+    let string = match format {
+        SchemaFormat::JsonSchema => serde_json::to_string_pretty(&to_json_schema(name, &columns))?,
+        SchemaFormat::Rust => to_rust_struct(name, &columns),
+    };
+    match output {
+        Some(output) => fs::write(output, string)?,
+        None => println!("{}", string.trim_end()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYER: &str = r#"use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Player {
+    pub name: String,
+    pub position: String,
+    #[serde(rename = "DOB")]
+    pub dob: String,
+    pub nationality: String,
+    #[serde(rename = "Kit Number")]
+    pub kit_number: u8,
+}
+"#;
+
+    #[test]
+    fn test_infer_schema() -> anyhow::Result<()> {
+        let data = "id,score,active,note\n1,1.5,true,\n-3,2,false,hi\n";
+        let columns = infer_schema(data.as_bytes(), b',', 0)?;
+        assert_eq!(columns[0].ty, ColumnType::Integer { min: -3, max: 1 });
+        assert_eq!(columns[1].ty, ColumnType::Float);
+        assert_eq!(columns[2].ty, ColumnType::Boolean);
+        assert_eq!(columns[3].ty, ColumnType::String);
+        assert!(columns[3].nullable);
+        assert!(!columns[0].nullable);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rust_struct_from_juventus() -> anyhow::Result<()> {
+        let reader = crate::utils::get_reader("assets/juventus.csv")?;
+        let columns = infer_schema(reader, b',', 0)?;
+        assert_eq!(to_rust_struct("Player", &columns), PLAYER);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rust_struct_duplicate_fields() -> anyhow::Result<()> {
+        let data = "Date of Birth,date_of_birth,date_of_birth_2\n2000-01-01,x,1\n";
+        let columns = infer_schema(data.as_bytes(), b',', 0)?;
+        let code = to_rust_struct("Record", &columns);
+        assert!(code
+            .contains("    #[serde(rename = \"Date of Birth\")]\n    pub date_of_birth: String,"));
+        assert!(code.contains(
+            "    #[serde(rename = \"date_of_birth\")]\n    pub date_of_birth_2: String,"
+        ));
+        assert!(code.contains(
+            "    #[serde(rename = \"date_of_birth_2\")]\n    pub date_of_birth_2_2: u8,"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_schema() -> anyhow::Result<()> {
+        let columns = infer_schema("a,b\n1,\n2,x\n".as_bytes(), b',', 0)?;
+        let schema = to_json_schema("Row", &columns);
+        assert_eq!(schema["properties"]["a"]["type"], "integer");
+        assert_eq!(schema["properties"]["b"]["type"], json!(["string", "null"]));
+        assert_eq!(schema["required"], json!(["a"]));
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+use anyhow::Result;
+use base64::prelude::*;
+use percent_encoding::percent_decode_str;
+use std::path::Path;
+
+use crate::cli::base64::Base64Format;
+use crate::process::base64_decode;
+
+const DEFAULT_MIME: &str = "text/plain;charset=US-ASCII";
+
+// every (offset, bytes) part of a signature has to match
+type Signature = &'static [(usize, &'static [u8])];
+
+const MAGIC: [(Signature, &str); 15] = [
+    (&[(0, b"\x89PNG\r\n\x1a\n")], "image/png"),
+    (&[(0, b"\xff\xd8\xff")], "image/jpeg"),
+    (&[(0, b"GIF87a")], "image/gif"),
+    (&[(0, b"GIF89a")], "image/gif"),
+    (&[(0, b"RIFF"), (8, b"WEBP")], "image/webp"),
+    (&[(4, b"ftypavif")], "image/avif"),
+    (&[(0, b"\x00\x00\x01\x00")], "image/x-icon"),
+    (&[(0, b"%PDF-")], "application/pdf"),
+    (&[(0, b"wOFF")], "font/woff"),
+    (&[(0, b"wOF2")], "font/woff2"),
+    (&[(0, b"\x00\x01\x00\x00\x00")], "font/ttf"),
+    (&[(0, b"OTTO")], "font/otf"),
+    (&[(0, b"\x00asm")], "application/wasm"),
+    (&[(0, b"ID3")], "audio/mpeg"),
+    (&[(4, b"ftyp")], "video/mp4"),
+];
+
+// "BM" alone is too common in text, also require zero reserved fields and a
+// known DIB header size
+fn is_bmp(data: &[u8]) -> bool {
+    const DIB_HEADER_SIZES: [u32; 7] = [12, 40, 52, 56, 64, 108, 124];
+    match data.get(..18) {
+        Some(header) if header.starts_with(b"BM") && header[6..10] == [0; 4] => {
+            let size = u32::from_le_bytes([header[14], header[15], header[16], header[17]]);
+            DIB_HEADER_SIZES.contains(&size)
+        }
+        _ => false,
+    }
+}
+
+const EXTENSIONS: [(&str, &str); 14] = [
+    ("svg", "image/svg+xml"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("ico", "image/x-icon"),
+    ("css", "text/css"),
+    ("js", "text/javascript"),
+    ("json", "application/json"),
+    ("html", "text/html"),
+    ("txt", "text/plain"),
+    ("woff2", "font/woff2"),
+    ("wasm", "application/wasm"),
+];
+
+// only a document whose root is `<svg`, so html with inline svg is left alone
+fn is_svg(data: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&data[..data.len().min(512)]);
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    let head = match head.strip_prefix("<?xml") {
+        Some(rest) => rest
+            .split_once("?>")
+            .map_or("", |(_, rest)| rest.trim_start()),
+        None => head,
+    };
+    head.starts_with("<svg")
+}
+
+pub fn sniff_mime(data: &[u8], path: Option<&str>) -> &'static str {
+    let magic = MAGIC.iter().find(|(parts, _)| {
+        parts
+            .iter()
+            .all(|(offset, magic)| data.get(*offset..offset + magic.len()) == Some(magic))
+    });
+    if let Some((_, mime)) = magic {
+        return mime;
+    }
+    if is_bmp(data) {
+        return "image/bmp";
+    }
+    if is_svg(data) {
+        return "image/svg+xml";
+    }
+    let extension = path
+        .and_then(|path| Path::new(path).extension())
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+    match extension {
+        Some(ext) => EXTENSIONS
+            .iter()
+            .find(|(e, _)| *e == ext)
+            .map_or("application/octet-stream", |(_, mime)| mime),
+        None => "application/octet-stream",
+    }
+}
+
+pub fn to_data_uri(data: &[u8], mime: &str) -> String {
+    format!("data:{};base64,{}", mime, BASE64_STANDARD.encode(data))
+}
+
+pub fn parse_data_uri(uri: &str) -> Result<(String, Vec<u8>)> {
+    let uri = uri.trim();
+    let rest = uri
+        .get(..5)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("data:"))
+        .map(|_| &uri[5..])
+        .ok_or_else(|| anyhow::anyhow!("not a data URI"))?;
+    let (meta, payload) = rest
+        .split_once(',')
+        .ok_or_else(|| anyhow::anyhow!("data URI has no payload"))?;
+    let (mime, is_base64) = match meta.strip_suffix(";base64") {
+        Some(mime) => (mime, true),
+        None => (meta, false),
+    };
+    let mime = if mime.is_empty() { DEFAULT_MIME } else { mime };
+    let data = if is_base64 {
+        let payload = percent_decode_str(payload).collect::<Vec<_>>();
+        let mut data = Vec::new();
+        base64_decode(&mut payload.as_slice(), &mut data, Base64Format::Auto)?;
+        data
+    } else {
+        percent_decode_str(payload).collect()
+    };
+    Ok((mime.to_string(), data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_mime() {
+        assert_eq!(
+            sniff_mime(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", None),
+            "image/png"
+        );
+        assert_eq!(sniff_mime(b"RIFF\0\0\0\0WEBPVP8 ", None), "image/webp");
+        assert_eq!(
+            sniff_mime(b"12345678WEBP", None),
+            "application/octet-stream"
+        );
+        let bmp = b"BM\x46\0\0\0\0\0\0\0\x36\0\0\0\x28\0\0\0";
+        assert_eq!(sniff_mime(bmp, None), "image/bmp");
+        assert_eq!(
+            sniff_mime(b"BMW owners club meeting notes", Some("notes.txt")),
+            "text/plain"
+        );
+        assert_eq!(sniff_mime(b"<?xml?><svg xmlns=''/>", None), "image/svg+xml");
+        assert_eq!(sniff_mime(b"  <svg/>", Some("icon.txt")), "image/svg+xml");
+        let page = b"<!doctype html><body><svg width='1'/></body>";
+        assert_eq!(sniff_mime(page, Some("page.html")), "text/html");
+        assert_eq!(sniff_mime(page, None), "application/octet-stream");
+        assert_eq!(sniff_mime(b"body{}", Some("site.CSS")), "text/css");
+        assert_eq!(
+            sniff_mime(b"\x01\x02", Some("blob")),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn test_data_uri_roundtrip() -> Result<()> {
+        let data = b"GIF89a\x01\x00\x01\x00\x00\xff\x00,";
+        let uri = to_data_uri(data, sniff_mime(data, None));
+        assert!(uri.starts_with("data:image/gif;base64,R0lGODlh"));
+        assert_eq!(
+            parse_data_uri(&uri)?,
+            ("image/gif".to_string(), data.to_vec())
+        );
+
+        let (mime, data) = parse_data_uri("data:,Hello%2C%20World!")?;
+        assert_eq!(mime, DEFAULT_MIME);
+        assert_eq!(data, b"Hello, World!");
+        assert!(parse_data_uri("http://example.com").is_err());
+        Ok(())
+    }
+}
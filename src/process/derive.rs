@@ -0,0 +1,104 @@
+use anyhow::Result;
+use argon2::{Algorithm, Argon2, Params, Version};
+use std::io::Read;
+
+use crate::cli::genpass::DeriveKdf;
+use crate::process::encoding::encode_fields;
+use crate::process::genpass::{build_password, PasswordPolicy};
+
+const DERIVE_CONTEXT: &str = "rcli 2024-11-01 genpass derive";
+// pinned instead of Params::default() so a crate update never changes derived passwords
+const ARGON2_M_COST: u32 = 19 * 1024;
+const ARGON2_T_COST: u32 = 2;
+const ARGON2_P_COST: u32 = 1;
+
+pub fn derive_password(
+    master: &str,
+    site: &str,
+    login: &str,
+    counter: u32,
+    kdf: DeriveKdf,
+    policy: &PasswordPolicy,
+) -> Result<String> {
+    let site = site.trim().to_lowercase();
+    let info = encode_fields(&[site.as_bytes(), login.as_bytes(), &counter.to_le_bytes()]);
+
+    let mut stream = match kdf {
+        DeriveKdf::Argon2id => {
+            let salt = blake3::derive_key(DERIVE_CONTEXT, &info);
+            let mut key = [0u8; 32];
+            let params = Params::new(ARGON2_M_COST, ARGON2_T_COST, ARGON2_P_COST, Some(key.len()))
+                .map_err(|e| anyhow::anyhow!("argon2 error {}", e))?;
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password_into(master.as_bytes(), &salt, &mut key)
+                .map_err(|e| anyhow::anyhow!("argon2 error {}", e))?;
+            blake3::Hasher::new_keyed(&key).finalize_xof()
+        }
+        DeriveKdf::Blake3 => {
+            let mut hasher = blake3::Hasher::new_derive_key(DERIVE_CONTEXT);
+            hasher.update(&encode_fields(&[master.as_bytes()]));
+            hasher.update(&info);
+            hasher.finalize_xof()
+        }
+    };
+
+    // rejection sampling keeps every index equally likely
+    build_password(policy, |n| {
+        let zone = u32::MAX - (u32::MAX % n as u32 + 1) % n as u32;
+        loop {
+            let mut buf = [0u8; 4];
+            stream
+                .read_exact(&mut buf)
+                .expect("blake3 xof never runs out");
+            let x = u32::from_le_bytes(buf);
+            if x <= zone {
+                return x as usize % n;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_password_is_stable() -> Result<()> {
+        let policy = PasswordPolicy {
+            length: 16,
+            ..Default::default()
+        };
+        let derive = |site, counter, kdf| {
+            derive_password("correct horse", site, "alice", counter, kdf, &policy)
+        };
+        let p1 = derive("Example.com", 1, DeriveKdf::Blake3)?;
+        assert_eq!(p1, derive("example.com", 1, DeriveKdf::Blake3)?);
+        assert_eq!(p1.len(), 16);
+        assert_ne!(p1, derive("example.com", 2, DeriveKdf::Blake3)?);
+        assert_ne!(p1, derive("example.org", 1, DeriveKdf::Blake3)?);
+
+        let p2 = derive("example.com", 1, DeriveKdf::Argon2id)?;
+        assert_eq!(p2, derive("example.com", 1, DeriveKdf::Argon2id)?);
+        assert_ne!(p1, p2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_password_known_answers() -> Result<()> {
+        let policy = PasswordPolicy {
+            length: 20,
+            max_repeat: Some(1),
+            ..Default::default()
+        };
+        // any change to the kdf parameters, build_password or limit_repeat breaks these
+        for (kdf, expected) in [
+            (DeriveKdf::Blake3, "tC6elaZVEPSOYG@4g%qs"),
+            (DeriveKdf::Argon2id, "aWxQ%JBh=d8FA7T3_#kf"),
+        ] {
+            let password =
+                derive_password("correct horse", "example.com", "alice", 1, kdf, &policy)?;
+            assert_eq!(password, expected);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+// length-prefixed so that ("ab", "c") and ("a", "bc") never collide, every
+// field is its u64 little-endian length followed by its bytes
+pub(crate) fn encode_fields(fields: &[&[u8]]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for field in fields {
+        buf.extend_from_slice(&(field.len() as u64).to_le_bytes());
+        buf.extend_from_slice(field);
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_fields_layout() {
+        assert_eq!(
+            encode_fields(&[b"ab", b""]),
+            [2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b', 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_ne!(encode_fields(&[b"ab", b"c"]), encode_fields(&[b"a", b"bc"]));
+        assert!(encode_fields(&[]).is_empty());
+    }
+}
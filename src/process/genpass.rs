@@ -1,10 +1,192 @@
 use anyhow::Result;
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 
-const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
-const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
-const NUMBER: &[u8] = b"0123456789";
-const SYMBOL: &[u8] = b"~!@#$%^&*()_-+=";
+use crate::cli::genpass::GenPassFormat;
+
+pub const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+pub const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+pub const NUMBER: &[u8] = b"0123456789";
+pub const SYMBOL: &[u8] = b"~!@#$%^&*()_-+=";
+pub const AMBIGUOUS: &[u8] = b"0O1lI";
+pub const CONSONANT: &[u8] = b"bcdfghjklmnprstvz";
+pub const VOWEL: &[u8] = b"aeiou";
+pub const MAX_LEN: u16 = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PasswordPolicy {
+    pub length: u16,
+    pub upper: bool,
+    pub lower: bool,
+    pub number: bool,
+    pub symbol: bool,
+    pub min_upper: u16,
+    pub min_lower: u16,
+    pub min_number: u16,
+    pub min_symbol: u16,
+    pub symbols: Option<String>,
+    pub exclude_ambiguous: bool,
+    pub exclude: Option<String>,
+    pub max_repeat: Option<u16>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            length: 8,
+            upper: true,
+            lower: true,
+            number: true,
+            symbol: true,
+            min_upper: 1,
+            min_lower: 1,
+            min_number: 1,
+            min_symbol: 1,
+            symbols: None,
+            exclude_ambiguous: false,
+            exclude: None,
+            max_repeat: None,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        let policy: Self = serde_yaml::from_str(content)?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    // (name, pool, min count) for every enabled class
+    pub fn classes(&self) -> Result<Vec<(&'static str, Vec<u8>, u16)>> {
+        let mut excluded = self
+            .exclude
+            .as_deref()
+            .unwrap_or_default()
+            .bytes()
+            .collect::<BTreeSet<_>>();
+        if self.exclude_ambiguous {
+            excluded.extend(AMBIGUOUS);
+        }
+        let symbols = match &self.symbols {
+            Some(symbols) => {
+                if let Some(c) = symbols
+                    .chars()
+                    .find(|c| !c.is_ascii_graphic() || c.is_ascii_alphanumeric())
+                {
+                    return Err(anyhow::anyhow!("invalid symbol {:?} in symbol set", c));
+                }
+                symbols.as_bytes()
+            }
+            None => SYMBOL,
+        };
+
+        let mut classes = Vec::new();
+        for (name, enabled, pool, min) in [
+            ("upper", self.upper, UPPER, self.min_upper),
+            ("lower", self.lower, LOWER, self.min_lower),
+            ("number", self.number, NUMBER, self.min_number),
+            ("symbol", self.symbol, symbols, self.min_symbol),
+        ] {
+            if !enabled {
+                continue;
+            }
+            let pool = pool
+                .iter()
+                .copied()
+                .filter(|c| !excluded.contains(c))
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            if pool.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "no {} characters left after exclusions",
+                    name
+                ));
+            }
+            classes.push((name, pool, min));
+        }
+        Ok(classes)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.length == 0 || self.length > MAX_LEN {
+            return Err(anyhow::anyhow!(
+                "length must be between 1 and {}, got {}",
+                MAX_LEN,
+                self.length
+            ));
+        }
+        if !(self.upper || self.lower || self.number || self.symbol) {
+            return Err(anyhow::anyhow!(
+                "at least one character class must be enabled"
+            ));
+        }
+        let classes = self.classes()?;
+        let required = classes.iter().map(|(_, _, min)| *min as u32).sum::<u32>();
+        if required > self.length as u32 {
+            return Err(anyhow::anyhow!(
+                "length {} is shorter than the {} required characters",
+                self.length,
+                required
+            ));
+        }
+        match self.max_repeat {
+            Some(0) => Err(anyhow::anyhow!("max_repeat must be greater than 0")),
+            Some(_) if classes.iter().map(|(_, pool, _)| pool.len()).sum::<usize>() < 2 => Err(
+                anyhow::anyhow!("max_repeat needs at least 2 distinct characters"),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl PasswordPolicy {
+    // upper bound ignoring the per-class minimums
+    pub fn entropy_bits(&self) -> Result<f64> {
+        let pool = self
+            .classes()?
+            .iter()
+            .map(|(_, pool, _)| pool.len())
+            .sum::<usize>();
+        Ok(self.length as f64 * (pool as f64).log2())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PasswordEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub password: String,
+    pub score: u8,
+    pub entropy_bits: f64,
+}
+
+pub fn format_entries(entries: &[PasswordEntry], format: GenPassFormat) -> Result<String> {
+    let output = match format {
+        GenPassFormat::Plain => entries
+            .iter()
+            .map(|e| match &e.label {
+                Some(label) => format!("{}\t{}", label, e.password),
+                None => e.password.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        GenPassFormat::Json => serde_json::to_string_pretty(entries)?,
+        GenPassFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for entry in entries {
+                writer.serialize(entry)?;
+            }
+            String::from_utf8(writer.into_inner()?)?
+                .trim_end()
+                .to_string()
+        }
+    };
+    Ok(output)
+}
 
 pub fn genpass(
     len: u8,
@@ -13,39 +195,194 @@ pub fn genpass(
     no_number: bool,
     no_symbol: bool,
 ) -> Result<String> {
+    let policy = PasswordPolicy {
+        length: len as u16,
+        upper: !no_upper,
+        lower: !no_lower,
+        number: !no_number,
+        symbol: !no_symbol,
+        ..Default::default()
+    };
+    generate_password(&policy)
+}
+
+pub fn generate_password(policy: &PasswordPolicy) -> Result<String> {
+    let mut rng = crate::utils::rng();
+    build_password(policy, |n| rng.gen_range(0..n))
+}
+
+// `pick(n)` must return a uniformly distributed index in 0..n
+pub fn build_password(
+    policy: &PasswordPolicy,
+    mut pick: impl FnMut(usize) -> usize,
+) -> Result<String> {
+    policy.validate()?;
     let mut chars = Vec::new();
-    let mut password = Vec::new();
-    let mut rng = rand::thread_rng();
-    if !no_upper {
-        chars.extend_from_slice(UPPER);
-        let c = *UPPER.choose(&mut rng).expect("char must not empty");
-        password.push(c);
+    let mut password = Vec::with_capacity(policy.length as usize);
+    for (_, pool, min) in policy.classes()? {
+        for _ in 0..min {
+            password.push(pool[pick(pool.len())]);
+        }
+        chars.extend(pool);
     }
 
-    if !no_lower {
-        chars.extend_from_slice(LOWER);
-        let c = *LOWER.choose(&mut rng).expect("char must not empty");
-        password.push(c);
+    while password.len() < policy.length as usize {
+        password.push(chars[pick(chars.len())]);
+    }
+    for i in (1..password.len()).rev() {
+        password.swap(i, pick(i + 1));
     }
+    if let Some(max_repeat) = policy.max_repeat {
+        limit_repeat(&mut password, max_repeat as usize)?;
+    }
+    Ok(String::from_utf8(password)?)
+}
+
+pub fn generate_pronounceable(policy: &PasswordPolicy) -> Result<(String, f64)> {
+    let mut rng = crate::utils::rng();
+    build_pronounceable(policy, |n| rng.gen_range(0..n))
+}
 
-    if !no_number {
-        chars.extend_from_slice(NUMBER);
-        let c = *NUMBER.choose(&mut rng).expect("char must not empty");
-        password.push(c);
+// consonant-vowel syllables with min_upper random capitals, the required digits
+// and symbols are inserted at random positions; entropy only counts the random choices
+pub fn build_pronounceable(
+    policy: &PasswordPolicy,
+    mut pick: impl FnMut(usize) -> usize,
+) -> Result<(String, f64)> {
+    policy.validate()?;
+    if !policy.upper && !policy.lower {
+        return Err(anyhow::anyhow!("pronounceable passwords need letters"));
+    }
+    let classes = policy.classes()?;
+    let extra = classes
+        .iter()
+        .filter(|(name, _, _)| *name == "number" || *name == "symbol")
+        .collect::<Vec<_>>();
+    let letters =
+        policy.length as usize - extra.iter().map(|(_, _, min)| *min as usize).sum::<usize>();
+    if letters == 0 {
+        return Err(anyhow::anyhow!("no room left for letters"));
     }
 
-    if !no_symbol {
-        chars.extend_from_slice(SYMBOL);
-        let c = *SYMBOL.choose(&mut rng).expect("char must not empty");
-        password.push(c);
+    let pool_of = |name| {
+        classes
+            .iter()
+            .find(|(n, _, _)| *n == name)
+            .map(|(_, pool, _)| pool.as_slice())
+            .unwrap_or_default()
+    };
+    let (lower, upper) = (pool_of("lower"), pool_of("upper"));
+    let allowed = |c: &u8| {
+        if policy.lower {
+            lower.contains(c)
+        } else {
+            upper.contains(&c.to_ascii_uppercase())
+        }
+    };
+    let consonants = CONSONANT
+        .iter()
+        .copied()
+        .filter(allowed)
+        .collect::<Vec<_>>();
+    let vowels = VOWEL.iter().copied().filter(allowed).collect::<Vec<_>>();
+    if consonants.is_empty() || vowels.is_empty() {
+        return Err(anyhow::anyhow!(
+            "no consonants or vowels left after exclusions"
+        ));
     }
 
-    for _ in 0..(len - password.len() as u8) {
-        let c = *chars.choose(&mut rng).expect("char must not empty");
-        password.push(c);
+    let mut password = Vec::with_capacity(policy.length as usize);
+    let mut entropy = 0.0;
+    for i in 0..letters {
+        let pool = if i % 2 == 0 { &consonants } else { &vowels };
+        let c = pool[pick(pool.len())];
+        password.push(if policy.lower {
+            c
+        } else {
+            c.to_ascii_uppercase()
+        });
+        entropy += (pool.len() as f64).log2();
     }
-    password.shuffle(&mut rng);
-    Ok(String::from_utf8(password)?)
+    if policy.lower && policy.upper {
+        let min_upper = policy.min_upper as usize;
+        let mut eligible = (0..letters)
+            .filter(|&i| upper.contains(&password[i].to_ascii_uppercase()))
+            .collect::<Vec<_>>();
+        if eligible.len() < min_upper {
+            return Err(anyhow::anyhow!(
+                "cannot capitalize {} letters after exclusions",
+                min_upper
+            ));
+        }
+        entropy += log2_binomial(eligible.len(), min_upper);
+        for _ in 0..min_upper {
+            let i = eligible.swap_remove(pick(eligible.len()));
+            password[i] = password[i].to_ascii_uppercase();
+        }
+    }
+
+    let mut chars = Vec::new();
+    for (_, pool, min) in extra {
+        for _ in 0..*min {
+            chars.push(pool[pick(pool.len())]);
+            entropy += (pool.len() as f64).log2();
+        }
+    }
+    // letters alternate consonant/vowel, so only the inserted characters can form runs
+    let max_repeat = policy.max_repeat.map_or(usize::MAX, |n| n as usize);
+    for (k, &c) in chars.iter().enumerate() {
+        let positions = (0..=password.len())
+            .filter(|&at| run_length(&password, at, c) <= max_repeat)
+            .collect::<Vec<_>>();
+        if positions.is_empty() {
+            return Err(anyhow::anyhow!("cannot satisfy max_repeat {}", max_repeat));
+        }
+        password.insert(positions[pick(positions.len())], c);
+        // only the set of positions counts, not the order the characters were placed in
+        entropy += (positions.len() as f64).log2() - ((k + 1) as f64).log2();
+    }
+    Ok((String::from_utf8(password)?, entropy))
+}
+
+// length of the run `c` would be part of when inserted at `at`
+fn run_length(password: &[u8], at: usize, c: u8) -> usize {
+    let before = password[..at].iter().rev().take_while(|&&b| b == c).count();
+    let after = password[at..].iter().take_while(|&&b| b == c).count();
+    before + 1 + after
+}
+
+fn log2_binomial(n: usize, k: usize) -> f64 {
+    (0..k)
+        .map(|i| ((n - i) as f64 / (i + 1) as f64).log2())
+        .sum()
+}
+
+// break up runs longer than max_repeat by swapping in a later, different character
+fn limit_repeat(password: &mut [u8], max_repeat: usize) -> Result<()> {
+    let mut run = 1;
+    for i in 1..password.len() {
+        if password[i] != password[i - 1] {
+            run = 1;
+            continue;
+        }
+        run += 1;
+        if run <= max_repeat {
+            continue;
+        }
+        let j = (i + 1..password.len())
+            .find(|&j| password[j] != password[i])
+            .or_else(|| {
+                (0..i).find(|&j| {
+                    password[j] != password[i]
+                        && (j == 0 || password[j - 1] != password[i])
+                        && password[j + 1] != password[i]
+                })
+            })
+            .ok_or_else(|| anyhow::anyhow!("cannot satisfy max_repeat {}", max_repeat))?;
+        password.swap(i, j);
+        run = 1;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -67,4 +404,116 @@ mod tests {
         let password = password.unwrap();
         assert_eq!(password.len() as u8, len);
     }
+
+    #[test]
+    fn test_genpass_invalid_policy() {
+        assert!(genpass(2, false, false, false, false).is_err());
+        assert!(genpass(8, true, true, true, true).is_err());
+        let policy = PasswordPolicy {
+            length: MAX_LEN + 1,
+            ..Default::default()
+        };
+        assert!(generate_password(&policy).is_err());
+    }
+
+    #[test]
+    fn test_generate_password_policy() -> Result<()> {
+        let policy = PasswordPolicy::from_yaml(
+            "length: 1024\nmin_number: 10\nsymbols: \"!?\"\nexclude_ambiguous: true\nmax_repeat: 1\n",
+        )?;
+        let password = generate_password(&policy)?;
+        assert_eq!(password.len(), 1024);
+        assert!(password.bytes().filter(|c| c.is_ascii_digit()).count() >= 10);
+        assert!(!password.bytes().any(|c| AMBIGUOUS.contains(&c)));
+        assert!(password
+            .bytes()
+            .filter(|c| c.is_ascii_punctuation())
+            .all(|c| c == b'!' || c == b'?'));
+        assert!(password.as_bytes().windows(2).all(|w| w[0] != w[1]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_pronounceable() -> Result<()> {
+        let policy = PasswordPolicy {
+            length: 12,
+            ..Default::default()
+        };
+        let (password, entropy) = generate_pronounceable(&policy)?;
+        let bytes = password.as_bytes();
+        assert_eq!(bytes.len(), 12);
+        assert_eq!(bytes.iter().filter(|c| c.is_ascii_uppercase()).count(), 1);
+        assert_eq!(bytes.iter().filter(|c| c.is_ascii_digit()).count(), 1);
+        assert_eq!(bytes.iter().filter(|c| SYMBOL.contains(c)).count(), 1);
+        let expected = 5.0 * 17f64.log2()
+            + 5.0 * 5f64.log2()
+            + 10f64.log2()
+            + 10f64.log2()
+            + 15f64.log2()
+            + 66f64.log2();
+        assert!((entropy - expected).abs() < 1e-9);
+
+        // always taking the first choice puts the digit and symbol in front
+        let (password, _) = build_pronounceable(&policy, |_| 0)?;
+        assert_eq!(password, "!0Bababababa");
+        Ok(())
+    }
+
+    #[test]
+    fn test_pronounceable_policy() -> Result<()> {
+        let policy = PasswordPolicy {
+            length: 16,
+            min_upper: 3,
+            min_number: 4,
+            exclude: Some("023456789".into()),
+            max_repeat: Some(1),
+            ..Default::default()
+        };
+        for _ in 0..20 {
+            let (password, _) = generate_pronounceable(&policy)?;
+            let bytes = password.as_bytes();
+            assert_eq!(bytes.iter().filter(|c| c.is_ascii_uppercase()).count(), 3);
+            assert_eq!(bytes.iter().filter(|&&c| c == b'1').count(), 4);
+            assert!(bytes.windows(2).all(|w| w[0] != w[1]));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_password_with_seed() -> Result<()> {
+        let policy = PasswordPolicy {
+            length: 24,
+            ..Default::default()
+        };
+        let generate = || {
+            let mut rng = crate::utils::seeded_rng("fixtures");
+            build_password(&policy, |n| rng.gen_range(0..n))
+        };
+        assert_eq!(generate()?, generate()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_entries() -> Result<()> {
+        let entries = (1..=2)
+            .map(|i| PasswordEntry {
+                label: Some(format!("db-{}", i)),
+                password: format!("secret{}", i),
+                score: 2,
+                entropy_bits: 52.5,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            format_entries(&entries, GenPassFormat::Plain)?,
+            "db-1\tsecret1\ndb-2\tsecret2"
+        );
+        assert_eq!(
+            format_entries(&entries, GenPassFormat::Csv)?,
+            "label,password,score,entropy_bits\ndb-1,secret1,2,52.5\ndb-2,secret2,2,52.5"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&format_entries(&entries, GenPassFormat::Json)?)?;
+        assert_eq!(json[1]["password"], "secret2");
+        Ok(())
+    }
 }
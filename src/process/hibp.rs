@@ -0,0 +1,102 @@
+use anyhow::Result;
+use data_encoding::HEXUPPER;
+use sha1::{Digest, Sha1};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Seek, SeekFrom},
+    path::Path,
+};
+
+// a Pwned Passwords SHA-1 file ordered by hash, one `HASH:COUNT` per line
+pub struct HibpFile {
+    reader: BufReader<File>,
+    len: u64,
+}
+
+impl HibpFile {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            reader: BufReader::with_capacity(512, file),
+            len,
+        })
+    }
+
+    pub fn lookup(&mut self, password: &str) -> Result<u64> {
+        let hash = HEXUPPER.encode(&Sha1::digest(password.as_bytes()));
+        self.lookup_hash(&hash)
+    }
+
+    pub fn lookup_hash(&mut self, hash: &str) -> Result<u64> {
+        let hash = hash.to_uppercase();
+        // smallest offset whose next full line is not ordered before `hash`
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match self.line_at(mid)? {
+                Some(line) if line_hash(&line) < hash.as_str() => lo = mid + 1,
+                _ => hi = mid,
+            }
+        }
+        match self.line_at(lo)? {
+            Some(line) if line_hash(&line) == hash => {
+                let count = line
+                    .split_once(':')
+                    .map(|(_, count)| count.trim())
+                    .unwrap_or("1");
+                Ok(count
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("invalid count in line {:?}", line))?)
+            }
+            _ => Ok(0),
+        }
+    }
+
+    // the first full line starting at or after `pos`
+    fn line_at(&mut self, pos: u64) -> Result<Option<String>> {
+        let mut line = String::new();
+        if pos > 0 {
+            self.reader.seek(SeekFrom::Start(pos - 1))?;
+            self.reader.read_line(&mut line)?;
+            line.clear();
+        } else {
+            self.reader.seek(SeekFrom::Start(0))?;
+        }
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim_end().to_uppercase()))
+    }
+}
+
+fn line_hash(line: &str) -> &str {
+    line.split_once(':').map_or(line, |(hash, _)| hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hibp_lookup() -> Result<()> {
+        let mut hibp = HibpFile::open("fixtures/hibp.txt")?;
+        assert_eq!(hibp.lookup("password")?, 9545824);
+        assert_eq!(hibp.lookup("123456")?, 37359195);
+        assert_eq!(hibp.lookup("alice2024")?, 3);
+        assert_eq!(hibp.lookup("Language-Shrimp-Fatal-Buffalo")?, 0);
+        assert_eq!(
+            hibp.lookup_hash("00da01dd793780e9c81bbe9952eaeb106ec428f5")?,
+            705
+        );
+        assert_eq!(
+            hibp.lookup_hash("FFB9B8715BD3B10608B9094988D5A3DDD0FB3665")?,
+            2940
+        );
+        assert_eq!(
+            hibp.lookup_hash("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF")?,
+            0
+        );
+        Ok(())
+    }
+}
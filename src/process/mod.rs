@@ -1,9 +1,38 @@
+pub mod armor;
 pub mod base64;
+pub mod codec;
+pub mod compress;
 pub mod csv_convert;
+pub mod csv_schema;
+pub mod datauri;
+pub mod derive;
+pub mod encoding;
 pub mod genpass;
+pub mod hibp;
 pub mod http;
+pub mod otp;
+pub mod passphrase;
+pub mod pipe;
+pub mod random;
+pub mod signature;
+pub mod strength;
 pub mod text;
+pub mod token;
 
+pub use armor::*;
 pub use base64::*;
+pub use codec::*;
+pub use compress::*;
 pub use csv_convert::*;
+pub use csv_schema::*;
+pub use datauri::*;
+pub use derive::*;
 pub use genpass::*;
+pub use hibp::*;
+pub use otp::*;
+pub use passphrase::*;
+pub use pipe::*;
+pub use random::*;
+pub use signature::*;
+pub use strength::*;
+pub use token::*;
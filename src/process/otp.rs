@@ -0,0 +1,225 @@
+use anyhow::Result;
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cli::otp::OtpAlgorithm;
+
+pub fn generate_secret(len: usize) -> String {
+    let mut secret = vec![0u8; len];
+    rand::rngs::OsRng.fill_bytes(&mut secret);
+    BASE32_NOPAD.encode(&secret)
+}
+
+pub fn decode_secret(secret: &str) -> Result<Vec<u8>> {
+    let secret = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '=')
+        .collect::<String>()
+        .to_uppercase();
+    let secret = BASE32_NOPAD
+        .decode(secret.as_bytes())
+        .map_err(|e| anyhow::anyhow!("invalid base32 secret: {}", e))?;
+    if secret.is_empty() {
+        return Err(anyhow::anyhow!("secret must not be empty"));
+    }
+    Ok(secret)
+}
+
+fn hmac_digest<M: Mac + hmac::digest::KeyInit>(key: &[u8], message: &[u8]) -> Result<Vec<u8>> {
+    let mut mac = <M as hmac::digest::KeyInit>::new_from_slice(key)?;
+    mac.update(message);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+pub fn hotp(secret: &[u8], counter: u64, digits: u32, algorithm: OtpAlgorithm) -> Result<String> {
+    let message = counter.to_be_bytes();
+    let hash = match algorithm {
+        OtpAlgorithm::Sha1 => hmac_digest::<Hmac<Sha1>>(secret, &message)?,
+        OtpAlgorithm::Sha256 => hmac_digest::<Hmac<Sha256>>(secret, &message)?,
+        OtpAlgorithm::Sha512 => hmac_digest::<Hmac<Sha512>>(secret, &message)?,
+    };
+    // dynamic truncation, RFC 4226 section 5.3
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let code = u32::from_be_bytes(hash[offset..offset + 4].try_into()?) & 0x7fff_ffff;
+    Ok(format!(
+        "{:0width$}",
+        code % 10u32.pow(digits),
+        width = digits as usize
+    ))
+}
+
+pub fn unix_time() -> Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+pub fn totp(
+    secret: &[u8],
+    time: u64,
+    period: u64,
+    digits: u32,
+    algorithm: OtpAlgorithm,
+) -> Result<String> {
+    hotp(secret, time / period, digits, algorithm)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// returns the matching counter within `counter..=counter + window`
+pub fn verify_hotp(
+    secret: &[u8],
+    code: &str,
+    counter: u64,
+    window: u64,
+    digits: u32,
+    algorithm: OtpAlgorithm,
+) -> Result<Option<u64>> {
+    for c in counter..=counter.saturating_add(window) {
+        if constant_time_eq(
+            hotp(secret, c, digits, algorithm)?.as_bytes(),
+            code.as_bytes(),
+        ) {
+            return Ok(Some(c));
+        }
+    }
+    Ok(None)
+}
+
+// returns the matching step offset within `-skew..=skew`
+pub fn verify_totp(
+    secret: &[u8],
+    code: &str,
+    time: u64,
+    period: u64,
+    skew: u64,
+    digits: u32,
+    algorithm: OtpAlgorithm,
+) -> Result<Option<i64>> {
+    let step = time / period;
+    for c in step.saturating_sub(skew)..=step.saturating_add(skew) {
+        if constant_time_eq(
+            hotp(secret, c, digits, algorithm)?.as_bytes(),
+            code.as_bytes(),
+        ) {
+            return Ok(Some(c as i64 - step as i64));
+        }
+    }
+    Ok(None)
+}
+
+fn encode_uri_component(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn otpauth_uri(
+    secret: &[u8],
+    issuer: Option<&str>,
+    account: &str,
+    algorithm: OtpAlgorithm,
+    digits: u32,
+    period: u64,
+    counter: Option<u64>,
+) -> String {
+    let label = match issuer {
+        Some(issuer) => format!(
+            "{}:{}",
+            encode_uri_component(issuer),
+            encode_uri_component(account)
+        ),
+        None => encode_uri_component(account),
+    };
+    let mut uri = format!(
+        "otpauth://{}/{}?secret={}",
+        if counter.is_some() { "hotp" } else { "totp" },
+        label,
+        BASE32_NOPAD.encode(secret)
+    );
+    if let Some(issuer) = issuer {
+        uri.push_str(&format!("&issuer={}", encode_uri_component(issuer)));
+    }
+    uri.push_str(&format!("&algorithm={}&digits={}", algorithm, digits));
+    match counter {
+        Some(counter) => uri.push_str(&format!("&counter={}", counter)),
+        None => uri.push_str(&format!("&period={}", period)),
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA1_KEY: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_hotp_rfc4226() -> Result<()> {
+        let expected = ["755224", "287082", "359152", "969429", "338314"];
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(
+                &hotp(SHA1_KEY, counter as u64, 6, OtpAlgorithm::Sha1)?,
+                code
+            );
+        }
+        assert_eq!(
+            verify_hotp(SHA1_KEY, "969429", 1, 5, 6, OtpAlgorithm::Sha1)?,
+            Some(3)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_totp_rfc6238() -> Result<()> {
+        let sha256_key = b"12345678901234567890123456789012";
+        let sha512_key = b"1234567890123456789012345678901234567890123456789012345678901234";
+        assert_eq!(totp(SHA1_KEY, 59, 30, 8, OtpAlgorithm::Sha1)?, "94287082");
+        assert_eq!(
+            totp(sha256_key, 59, 30, 8, OtpAlgorithm::Sha256)?,
+            "46119246"
+        );
+        assert_eq!(
+            totp(sha512_key, 59, 30, 8, OtpAlgorithm::Sha512)?,
+            "90693936"
+        );
+        assert_eq!(
+            totp(SHA1_KEY, 1111111109, 30, 8, OtpAlgorithm::Sha1)?,
+            "07081804"
+        );
+
+        let code = totp(SHA1_KEY, 1111111109, 30, 6, OtpAlgorithm::Sha1)?;
+        let verify =
+            |time, skew| verify_totp(SHA1_KEY, &code, time, 30, skew, 6, OtpAlgorithm::Sha1);
+        assert_eq!(verify(1111111109 + 30, 1)?, Some(-1));
+        assert_eq!(verify(1111111109 + 60, 1)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_secret_and_uri() -> Result<()> {
+        let secret = generate_secret(20);
+        assert_eq!(decode_secret(&secret.to_lowercase())?.len(), 20);
+        let uri = otpauth_uri(
+            SHA1_KEY,
+            Some("ACME Co"),
+            "alice@example.com",
+            OtpAlgorithm::Sha1,
+            6,
+            30,
+            None,
+        );
+        assert_eq!(uri, "otpauth://totp/ACME%20Co:alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=ACME%20Co&algorithm=SHA1&digits=6&period=30");
+        Ok(())
+    }
+}
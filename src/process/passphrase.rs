@@ -0,0 +1,110 @@
+use anyhow::Result;
+use rand::prelude::*;
+use std::collections::HashSet;
+
+use crate::process::genpass::{NUMBER, SYMBOL};
+
+// BIP-39 stands in for the EFF large wordlist until that is vendored, see assets/README.md
+const DEFAULT_WORDLIST: &str = include_str!("../../assets/bip39_english.txt");
+
+pub fn default_wordlist() -> Vec<String> {
+    parse_wordlist(DEFAULT_WORDLIST).expect("built-in wordlist must be valid")
+}
+
+// accept plain lists and diceware lists like the EFF one (`11111\tabacus`)
+pub fn parse_wordlist(content: &str) -> Result<Vec<String>> {
+    let mut seen = HashSet::new();
+    let mut words = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let mut fields = line.split_whitespace();
+        let word = match (fields.next(), fields.next(), fields.next()) {
+            (None, _, _) => continue,
+            (Some(word), None, _) => word,
+            (Some(dice), Some(word), None) if dice.bytes().all(|b| b.is_ascii_digit()) => word,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "line {}: expected a word or a diceware entry, got {:?}",
+                    i + 1,
+                    line
+                ))
+            }
+        };
+        if seen.insert(word) {
+            words.push(word.to_string());
+        }
+    }
+
+    if words.len() < 2 {
+        return Err(anyhow::anyhow!(
+            "wordlist must contain at least 2 unique words"
+        ));
+    }
+    Ok(words)
+}
+
+pub fn genphrase(
+    wordlist: &[String],
+    words: u8,
+    separator: &str,
+    capitalize: bool,
+    append_number: bool,
+    append_symbol: bool,
+) -> Result<(String, f64)> {
+    if words == 0 {
+        return Err(anyhow::anyhow!("words must be greater than 0"));
+    }
+    let mut rng = crate::utils::rng();
+    let mut phrase = (0..words)
+        .map(|_| {
+            let word = wordlist.choose(&mut rng).expect("wordlist must not empty");
+            if capitalize {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|c| c.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            } else {
+                word.clone()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(separator);
+
+    let mut entropy = words as f64 * (wordlist.len() as f64).log2();
+    if append_number {
+        phrase.push(*NUMBER.choose(&mut rng).expect("char must not empty") as char);
+        entropy += (NUMBER.len() as f64).log2();
+    }
+    if append_symbol {
+        phrase.push(*SYMBOL.choose(&mut rng).expect("char must not empty") as char);
+        entropy += (SYMBOL.len() as f64).log2();
+    }
+    Ok((phrase, entropy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_wordlist() -> Result<()> {
+        let words = parse_wordlist("11111\tabacus\n11112\tabdomen\n\n11113\tabacus\n")?;
+        assert_eq!(words, vec!["abacus", "abdomen"]);
+        assert!(parse_wordlist("only\n").is_err());
+        let err = parse_wordlist("abacus\nice cream\n").unwrap_err();
+        assert!(err.to_string().starts_with("line 2:"));
+        assert_eq!(default_wordlist().len(), 2048);
+        Ok(())
+    }
+
+    #[test]
+    fn test_genphrase() -> Result<()> {
+        let wordlist = default_wordlist();
+        let (phrase, entropy) = genphrase(&wordlist, 6, "-", true, true, false)?;
+        assert_eq!(phrase.split('-').count(), 6);
+        assert!(phrase.chars().next().unwrap().is_uppercase());
+        assert!(phrase.chars().last().unwrap().is_ascii_digit());
+        assert!((entropy - (66.0 + 10f64.log2())).abs() < 1e-9);
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use base64::write::EncoderWriter;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use super::compress::{stage, Finish, BUF_SIZE};
+use crate::cli::{base64::Base64Format, compress::CompressFormat, pipe::PipeStep};
+use crate::process::{base64_reader, engine};
+
+impl PipeStep {
+    pub fn is_encoding(&self) -> bool {
+        matches!(self, PipeStep::Base64 | PipeStep::Base64url)
+    }
+
+    fn base64_format(&self) -> Base64Format {
+        match self {
+            PipeStep::Base64url => Base64Format::UrlSafeNoPad,
+            _ => Base64Format::Stand,
+        }
+    }
+
+    fn compression(&self) -> Option<CompressFormat> {
+        match self {
+            PipeStep::Gzip => Some(CompressFormat::Gzip),
+            PipeStep::Zlib => Some(CompressFormat::Zlib),
+            PipeStep::Deflate => Some(CompressFormat::Deflate),
+            PipeStep::Zstd => Some(CompressFormat::Zstd),
+            PipeStep::Brotli => Some(CompressFormat::Brotli),
+            PipeStep::Xz => Some(CompressFormat::Xz),
+            PipeStep::Base64 | PipeStep::Base64url => None,
+        }
+    }
+
+    pub fn encoder(&self, output: Box<dyn Finish>) -> Result<Box<dyn Finish>> {
+        match self.compression() {
+            Some(format) => format.encoder(output, None, None),
+            None => Ok(stage(
+                EncoderWriter::new(output, engine(self.base64_format())),
+                |mut w| w.finish()?.finish(),
+            )),
+        }
+    }
+
+    pub fn decoder(&self, input: Box<dyn Read>) -> Result<Box<dyn Read>> {
+        match self.compression() {
+            Some(format) => format.decoder(input, None),
+            None => Ok(Box::new(base64_reader(input, Base64Format::Auto))),
+        }
+    }
+}
+
+pub fn pipe_forward(
+    input: impl Read,
+    output: impl Write + 'static,
+    steps: &[PipeStep],
+) -> Result<u64> {
+    let output = BufWriter::with_capacity(BUF_SIZE, output);
+    // text output ends with a newline like base64 encode
+    let mut writer = match steps.last() {
+        Some(step) if step.is_encoding() => stage(output, |mut w| {
+            w.write_all(b"\n")?;
+            w.flush()
+        }),
+        _ => stage(output, |mut w| w.flush()),
+    };
+    for step in steps.iter().rev() {
+        writer = step.encoder(writer)?;
+    }
+    let n = io::copy(&mut BufReader::with_capacity(BUF_SIZE, input), &mut writer)?;
+    writer.finish()?;
+    Ok(n)
+}
+
+pub fn pipe_reverse(
+    input: impl Read + 'static,
+    output: impl Write,
+    steps: &[PipeStep],
+) -> Result<u64> {
+    let mut reader: Box<dyn Read> = Box::new(BufReader::with_capacity(BUF_SIZE, input));
+    for step in steps.iter().rev() {
+        reader = step.decoder(reader)?;
+    }
+    let mut output = BufWriter::with_capacity(BUF_SIZE, output);
+    let n = io::copy(&mut reader, &mut output)?;
+    output.flush()?;
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::MultiGzDecoder;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn forward(data: &[u8], steps: &[PipeStep]) -> Result<Vec<u8>> {
+        let output = Shared::default();
+        pipe_forward(data, output.clone(), steps)?;
+        let encoded = output.0.lock().unwrap().clone();
+        Ok(encoded)
+    }
+
+    #[test]
+    fn test_pipe_roundtrip() -> Result<()> {
+        let data = "the quick brown fox jumps over the lazy dog\n".repeat(5000);
+        let steps = [
+            PipeStep::Gzip,
+            PipeStep::Zlib,
+            PipeStep::Deflate,
+            PipeStep::Zstd,
+            PipeStep::Brotli,
+            PipeStep::Xz,
+            PipeStep::Base64,
+            PipeStep::Base64url,
+        ];
+        for chain in steps
+            .iter()
+            .flat_map(|a| steps.iter().map(move |b| [*a, *b]))
+        {
+            let encoded = forward(data.as_bytes(), &chain)?;
+            let mut decoded = Vec::new();
+            pipe_reverse(io::Cursor::new(encoded), &mut decoded, &chain)?;
+            assert_eq!(decoded, data.as_bytes(), "{:?}", chain);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_pipe_gzip_base64url() -> Result<()> {
+        let encoded = forward(b"hello hello hello", &[PipeStep::Gzip, PipeStep::Base64url])?;
+        assert!(encoded.starts_with(b"H4sI"));
+        assert!(encoded.ends_with(b"\n"));
+        assert!(encoded[..encoded.len() - 1]
+            .iter()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_".contains(b)));
+
+        let mut gzip = Vec::new();
+        base64_reader(&encoded[..], Base64Format::UrlSafeNoPad).read_to_end(&mut gzip)?;
+        let mut decoded = String::new();
+        MultiGzDecoder::new(&gzip[..]).read_to_string(&mut decoded)?;
+        assert_eq!(decoded, "hello hello hello");
+        Ok(())
+    }
+}
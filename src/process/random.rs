@@ -0,0 +1,175 @@
+use anyhow::Result;
+use base64::prelude::*;
+use data_encoding::HEXLOWER;
+use rand::prelude::*;
+
+use crate::cli::random::BytesEncoding;
+use crate::utils::rng;
+
+pub fn random_bytes(len: usize, encoding: BytesEncoding) -> String {
+    let mut bytes = vec![0u8; len];
+    rng().fill_bytes(&mut bytes);
+    match encoding {
+        BytesEncoding::Hex => HEXLOWER.encode(&bytes),
+        BytesEncoding::Base64 => BASE64_STANDARD.encode(&bytes),
+        BytesEncoding::Base64url => BASE64_URL_SAFE_NO_PAD.encode(&bytes),
+    }
+}
+
+pub fn random_ints(min: i64, max: i64, count: usize) -> Result<Vec<i64>> {
+    if min > max {
+        return Err(anyhow::anyhow!("min {} is greater than max {}", min, max));
+    }
+    let mut rng = rng();
+    Ok((0..count).map(|_| rng.gen_range(min..=max)).collect())
+}
+
+pub fn random_floats(min: f64, max: f64, count: usize) -> Result<Vec<f64>> {
+    // a span that overflows to infinity would make the sampler panic
+    if min >= max || !(max - min).is_finite() {
+        return Err(anyhow::anyhow!("invalid range {}..{}", min, max));
+    }
+    let mut rng = rng();
+    Ok((0..count).map(|_| rng.gen_range(min..max)).collect())
+}
+
+pub fn pick_lines(content: &str, count: usize) -> Vec<&str> {
+    let lines = content.lines().collect::<Vec<_>>();
+    lines.choose_multiple(&mut rng(), count).copied().collect()
+}
+
+pub fn shuffle_lines(content: &str) -> Vec<&str> {
+    let mut lines = content.lines().collect::<Vec<_>>();
+    lines.shuffle(&mut rng());
+    lines
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum DiceTerm {
+    Dice { sign: i64, count: u32, sides: u32 },
+    Constant(i64),
+}
+
+const MAX_DICE: u32 = 1000;
+
+fn parse_dice(notation: &str) -> Result<Vec<DiceTerm>> {
+    let notation = notation.replace(char::is_whitespace, "").to_lowercase();
+    let invalid = || anyhow::anyhow!("invalid dice notation {:?}", notation);
+    let mut terms = Vec::new();
+    // a leading sign applies to the first term, e.g. `-2+1d6`
+    let (mut sign, mut rest) = match notation.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, notation.strip_prefix('+').unwrap_or(&notation)),
+    };
+    loop {
+        let end = rest.find(['+', '-']).unwrap_or(rest.len());
+        let term = &rest[..end];
+        match term.split_once('d') {
+            Some((count, sides)) => {
+                let count = if count.is_empty() {
+                    1
+                } else {
+                    count.parse().map_err(|_| invalid())?
+                };
+                let sides = sides.parse().map_err(|_| invalid())?;
+                if count == 0 || count > MAX_DICE || sides == 0 {
+                    return Err(invalid());
+                }
+                terms.push(DiceTerm::Dice { sign, count, sides });
+            }
+            None => {
+                // parse the signed text so that i64::MIN stays representable
+                let n = if sign < 0 {
+                    format!("-{}", term).parse()
+                } else {
+                    term.parse()
+                };
+                terms.push(DiceTerm::Constant(n.map_err(|_| invalid())?));
+            }
+        }
+        if end == rest.len() {
+            break;
+        }
+        sign = if rest.as_bytes()[end] == b'-' { -1 } else { 1 };
+        rest = &rest[end + 1..];
+    }
+    Ok(terms)
+}
+
+// returns the total and every single die rolled
+pub fn roll_dice(notation: &str) -> Result<(i64, Vec<u32>)> {
+    let mut rng = rng();
+    let mut total: i64 = 0;
+    let mut rolls = Vec::new();
+    let overflow = || anyhow::anyhow!("dice total overflows");
+    for term in parse_dice(notation)? {
+        match term {
+            DiceTerm::Dice { sign, count, sides } => {
+                for _ in 0..count {
+                    let roll = rng.gen_range(1..=sides);
+                    rolls.push(roll);
+                    total = total.checked_add(sign * roll as i64).ok_or_else(overflow)?;
+                }
+            }
+            DiceTerm::Constant(n) => total = total.checked_add(n).ok_or_else(overflow)?,
+        }
+    }
+    Ok((total, rolls))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dice() -> Result<()> {
+        assert_eq!(
+            parse_dice("3d6 + 2")?,
+            vec![
+                DiceTerm::Dice {
+                    sign: 1,
+                    count: 3,
+                    sides: 6
+                },
+                DiceTerm::Constant(2)
+            ]
+        );
+        assert_eq!(parse_dice("d20-1")?.len(), 2);
+        assert!(parse_dice("3x6").is_err());
+        assert!(parse_dice("0d6").is_err());
+        assert!(parse_dice("3d6+").is_err());
+        assert_eq!(parse_dice("-2+1d6")?[0], DiceTerm::Constant(-2));
+        assert!(parse_dice("--2").is_err());
+        assert!(roll_dice("9223372036854775807+9223372036854775807").is_err());
+        assert!(roll_dice("-9223372036854775807-2").is_err());
+        assert_eq!(roll_dice("-9223372036854775808")?.0, i64::MIN);
+        assert!(parse_dice("1d6--").is_err());
+
+        let (total, rolls) = roll_dice("3d6+2")?;
+        assert_eq!(rolls.len(), 3);
+        assert!((5..=20).contains(&total));
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_ranges() -> Result<()> {
+        assert!(random_ints(-3, 3, 100)?
+            .iter()
+            .all(|n| (-3..=3).contains(n)));
+        assert!(random_ints(3, -3, 1).is_err());
+        assert!(random_floats(0.0, 1.0, 100)?
+            .iter()
+            .all(|n| (0.0..1.0).contains(n)));
+        assert!(random_floats(-1e308, 1e308, 1).is_err());
+        assert!(random_floats(0.0, f64::INFINITY, 1).is_err());
+        assert_eq!(random_bytes(32, BytesEncoding::Hex).len(), 64);
+
+        let lines = pick_lines("a\nb\nc\n", 2);
+        assert_eq!(lines.len(), 2);
+        assert_ne!(lines[0], lines[1]);
+        let mut shuffled = shuffle_lines("a\nb\nc\n");
+        shuffled.sort();
+        assert_eq!(shuffled, vec!["a", "b", "c"]);
+        Ok(())
+    }
+}
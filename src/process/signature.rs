@@ -0,0 +1,243 @@
+use anyhow::Result;
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read};
+
+use super::encoding::encode_fields;
+use crate::cli::text::TextSignFormat;
+use crate::process::{
+    dearmor,
+    text::{key_fingerprint, sign_text, verify_text},
+    Armor,
+};
+
+const LABEL: &str = "SIGNATURE";
+const CONTEXT: &[u8] = b"rcli 2024-11-01 detached signature";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetachedSignature {
+    pub algorithm: TextSignFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_fingerprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    pub signature: String,
+}
+
+impl DetachedSignature {
+    pub fn sign(
+        input: &mut dyn Read,
+        algorithm: TextSignFormat,
+        key: &[u8],
+        created: u64,
+        expires_in: Option<u64>,
+        comment: Option<String>,
+    ) -> Result<Self> {
+        let expires = expires_in
+            .map(|secs| created.checked_add(secs))
+            .map(|expires| expires.ok_or_else(|| anyhow::anyhow!("expiry is out of range")))
+            .transpose()?;
+        let mut detached = Self {
+            key_fingerprint: Some(key_fingerprint(key, algorithm.clone(), true)?),
+            algorithm,
+            created: Some(created),
+            expires,
+            comment,
+            signature: String::new(),
+        };
+        let payload = detached.payload(input)?;
+        let sig = sign_text(&mut payload.as_slice(), key, detached.algorithm.clone())?;
+        detached.signature = BASE64_URL_SAFE_NO_PAD.encode(sig);
+        Ok(detached)
+    }
+
+    // the metadata and a digest of the content, so none of the fields can be
+    // edited or dropped without breaking the signature
+    fn payload(&self, input: &mut dyn Read) -> Result<Vec<u8>> {
+        let mut hasher = blake3::Hasher::new();
+        io::copy(input, &mut hasher)?;
+        // an empty field for None, a 1 byte tag before Some values
+        let optional = |value: Option<&[u8]>| match value {
+            Some(value) => [&[1u8][..], value].concat(),
+            None => Vec::new(),
+        };
+        Ok(encode_fields(&[
+            CONTEXT,
+            self.algorithm.to_string().as_bytes(),
+            &optional(self.key_fingerprint.as_deref().map(str::as_bytes)),
+            &optional(self.created.map(u64::to_le_bytes).as_ref().map(|b| &b[..])),
+            &optional(self.expires.map(u64::to_le_bytes).as_ref().map(|b| &b[..])),
+            &optional(self.comment.as_deref().map(str::as_bytes)),
+            hasher.finalize().as_bytes(),
+        ]))
+    }
+
+    pub fn verify(&self, input: &mut dyn Read, key: &[u8], now: u64) -> Result<bool> {
+        self.check(key, now)?;
+        let payload = self.payload(input)?;
+        verify_text(
+            &mut payload.as_slice(),
+            key,
+            &self.signature()?,
+            self.algorithm.clone(),
+        )
+    }
+
+    pub fn signature(&self) -> Result<Vec<u8>> {
+        Ok(BASE64_URL_SAFE_NO_PAD.decode(&self.signature)?)
+    }
+
+    pub fn to_armor(&self) -> Result<Armor> {
+        let mut armor = Armor::new(LABEL, self.signature()?).header("Algorithm", &self.algorithm);
+        if let Some(fingerprint) = &self.key_fingerprint {
+            armor = armor.header("Key-Fingerprint", fingerprint);
+        }
+        if let Some(created) = self.created {
+            armor = armor.header("Created", created);
+        }
+        if let Some(expires) = self.expires {
+            armor = armor.header("Expires", expires);
+        }
+        if let Some(comment) = &self.comment {
+            armor = armor.header("Comment", comment);
+        }
+        Ok(armor)
+    }
+
+    fn from_armor(armor: Armor) -> Result<Self> {
+        let algorithm = armor
+            .get_header("Algorithm")
+            .ok_or_else(|| anyhow::anyhow!("armored signature has no Algorithm header"))?
+            .parse()?;
+        let number = |key| armor.get_header(key).map(str::parse).transpose();
+        Ok(Self {
+            algorithm,
+            key_fingerprint: armor.get_header("Key-Fingerprint").map(str::to_string),
+            created: number("Created")?,
+            expires: number("Expires")?,
+            comment: armor.get_header("Comment").map(str::to_string),
+            signature: BASE64_URL_SAFE_NO_PAD.encode(armor.into_data(LABEL)?),
+        })
+    }
+
+    // None for a bare base64 signature
+    pub fn parse(content: &[u8]) -> Result<Option<Self>> {
+        if content.trim_ascii_start().starts_with(b"{") {
+            return Ok(Some(serde_json::from_slice(content)?));
+        }
+        match dearmor(content)? {
+            Some(armor) => Ok(Some(Self::from_armor(armor)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn check(&self, key: &[u8], now: u64) -> Result<()> {
+        if let Some(expires) = self.expires {
+            if now >= expires {
+                return Err(anyhow::anyhow!("signature expired at {}", expires));
+            }
+        }
+        if let Some(expected) = &self.key_fingerprint {
+            let fingerprint = key_fingerprint(key, self.algorithm.clone(), false)?;
+            if *expected != fingerprint {
+                return Err(anyhow::anyhow!(
+                    "signature was made with key {}, not {}",
+                    expected,
+                    fingerprint
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SK: &[u8] = include_bytes!("../../fixtures/ed25519.sk");
+    const PK: &[u8] = include_bytes!("../../fixtures/ed25519.pk");
+
+    #[test]
+    fn test_detached_signature_roundtrip() -> Result<()> {
+        let sig = DetachedSignature::sign(
+            &mut &b"hello"[..],
+            TextSignFormat::Ed25519,
+            SK,
+            1_700_000_000,
+            Some(3600),
+            Some("release v1.0".to_string()),
+        )?;
+        assert_eq!(sig.expires, Some(1_700_003_600));
+
+        let json = serde_json::to_string_pretty(&sig)?;
+        assert!(json.contains("\"algorithm\": \"ed25519\""));
+        assert_eq!(
+            DetachedSignature::parse(json.as_bytes())?,
+            Some(sig.clone())
+        );
+
+        let pem = sig.to_armor()?.to_string();
+        assert!(pem.contains("Comment: release v1.0"));
+        assert_eq!(DetachedSignature::parse(pem.as_bytes())?, Some(sig.clone()));
+        assert_eq!(sig.signature()?.len(), 64);
+        assert!(DetachedSignature::parse(b"c2ln")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_detached_signature_check() -> Result<()> {
+        let sig = DetachedSignature::sign(
+            &mut &b"hello"[..],
+            TextSignFormat::Ed25519,
+            SK,
+            100,
+            Some(10),
+            None,
+        )?;
+        assert!(sig.verify(&mut &b"hello"[..], PK, 105)?);
+        assert!(!sig.verify(&mut &b"hellO"[..], PK, 105)?);
+        assert!(sig.verify(&mut &b"hello"[..], PK, 110).is_err());
+
+        // dropping or moving the expiry invalidates the signature
+        for expires in [None, Some(u64::MAX)] {
+            let tampered = DetachedSignature {
+                expires,
+                ..sig.clone()
+            };
+            assert!(!tampered.verify(&mut &b"hello"[..], PK, 105)?);
+        }
+        let tampered = DetachedSignature {
+            comment: Some("approved".to_string()),
+            ..sig.clone()
+        };
+        assert!(!tampered.verify(&mut &b"hello"[..], PK, 105)?);
+
+        let blake3 = include_bytes!("../../fixtures/blake3.key");
+        let other = DetachedSignature::sign(
+            &mut &b"hello"[..],
+            TextSignFormat::Blake3,
+            blake3,
+            100,
+            None,
+            None,
+        )?;
+        assert!(other.verify(&mut &b"hello"[..], blake3, u64::MAX)?);
+        assert!(other.check(&[1; 32], 105).is_err());
+
+        let overflow = DetachedSignature::sign(
+            &mut &b"hello"[..],
+            TextSignFormat::Blake3,
+            blake3,
+            100,
+            Some(u64::MAX),
+            None,
+        );
+        assert!(overflow.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+use serde::Serialize;
+use std::fmt;
+use zxcvbn::zxcvbn;
+
+use crate::process::hibp::HibpFile;
+
+#[derive(Debug, Serialize)]
+pub struct StrengthReport {
+    pub score: u8,
+    pub guesses: u64,
+    pub guesses_log10: f64,
+    pub entropy_bits: f64,
+    pub crack_times: CrackTimes,
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CrackTimes {
+    pub online_throttling_100_per_hour: String,
+    pub online_no_throttling_10_per_second: String,
+    pub offline_slow_hashing_1e4_per_second: String,
+    pub offline_fast_hashing_1e10_per_second: String,
+}
+
+pub fn check_strength(password: &str, user_inputs: &[&str]) -> StrengthReport {
+    let entropy = zxcvbn(password, user_inputs);
+    let crack_times = entropy.crack_times();
+    let (warning, suggestions) = match entropy.feedback() {
+        Some(feedback) => (
+            feedback.warning().map(|w| w.to_string()),
+            feedback
+                .suggestions()
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        ),
+        None => (None, Vec::new()),
+    };
+    StrengthReport {
+        score: entropy.score().into(),
+        guesses: entropy.guesses(),
+        guesses_log10: entropy.guesses_log10(),
+        entropy_bits: entropy.guesses_log10() * 10f64.log2(),
+        crack_times: CrackTimes {
+            online_throttling_100_per_hour: crack_times
+                .online_throttling_100_per_hour()
+                .to_string(),
+            online_no_throttling_10_per_second: crack_times
+                .online_no_throttling_10_per_second()
+                .to_string(),
+            offline_slow_hashing_1e4_per_second: crack_times
+                .offline_slow_hashing_1e4_per_second()
+                .to_string(),
+            offline_fast_hashing_1e10_per_second: crack_times
+                .offline_fast_hashing_1e10_per_second()
+                .to_string(),
+        },
+        warning,
+        suggestions,
+    }
+}
+
+impl fmt::Display for StrengthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "score : {}", self.score)?;
+        writeln!(
+            f,
+            "guesses : {} (10^{:.2})",
+            self.guesses, self.guesses_log10
+        )?;
+        writeln!(f, "crack times :")?;
+        writeln!(
+            f,
+            "  online, throttled (100/hour) : {}",
+            self.crack_times.online_throttling_100_per_hour
+        )?;
+        writeln!(
+            f,
+            "  online, unthrottled (10/s) : {}",
+            self.crack_times.online_no_throttling_10_per_second
+        )?;
+        writeln!(
+            f,
+            "  offline, slow hash (1e4/s) : {}",
+            self.crack_times.offline_slow_hashing_1e4_per_second
+        )?;
+        write!(
+            f,
+            "  offline, fast hash (1e10/s) : {}",
+            self.crack_times.offline_fast_hashing_1e10_per_second
+        )?;
+        if let Some(warning) = &self.warning {
+            write!(f, "\nwarning : {}", warning)?;
+        }
+        for suggestion in &self.suggestions {
+            write!(f, "\nsuggestion : {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEntry {
+    pub line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breached: Option<u64>,
+    pub rejected: bool,
+    #[serde(flatten)]
+    pub report: StrengthReport,
+}
+
+pub fn audit_passwords(
+    content: &str,
+    user_inputs: &[&str],
+    show: bool,
+    mut hibp: Option<&mut HibpFile>,
+) -> anyhow::Result<Vec<AuditEntry>> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, password)| !password.is_empty())
+        .map(|(line, password)| {
+            let breached = match hibp.as_deref_mut() {
+                Some(hibp) => Some(hibp.lookup(password)?),
+                None => None,
+            };
+            Ok(AuditEntry {
+                line,
+                password: show.then(|| password.to_string()),
+                breached,
+                rejected: breached.is_some_and(|n| n > 0),
+                report: check_strength(password, user_inputs),
+            })
+        })
+        .collect()
+}
+
+pub fn audit_table(entries: &[AuditEntry]) -> String {
+    let mut rows = vec![[
+        "line".to_string(),
+        "password".to_string(),
+        "score".to_string(),
+        "entropy".to_string(),
+        "offline slow".to_string(),
+        "offline fast".to_string(),
+        "breached".to_string(),
+        "status".to_string(),
+        "warning".to_string(),
+    ]];
+    for entry in entries {
+        rows.push([
+            entry.line.to_string(),
+            entry.password.clone().unwrap_or_else(|| "*".repeat(8)),
+            entry.report.score.to_string(),
+            format!("{:.1}", entry.report.entropy_bits),
+            entry
+                .report
+                .crack_times
+                .offline_slow_hashing_1e4_per_second
+                .clone(),
+            entry
+                .report
+                .crack_times
+                .offline_fast_hashing_1e10_per_second
+                .clone(),
+            entry
+                .breached
+                .map_or_else(|| "-".to_string(), |n| n.to_string()),
+            if entry.rejected { "rejected" } else { "ok" }.to_string(),
+            entry.report.warning.clone().unwrap_or_default(),
+        ]);
+    }
+
+    let mut widths = [0; 9];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_strength() {
+        let weak = check_strength("password", &[]);
+        assert_eq!(weak.score, 0);
+        assert!(weak.warning.is_some());
+
+        let strong = check_strength("Language-Shrimp-Fatal-Buffalo", &[]);
+        assert_eq!(strong.score, 4);
+        assert!(strong.guesses > weak.guesses);
+    }
+
+    #[test]
+    fn test_audit_passwords() -> anyhow::Result<()> {
+        let mut hibp = HibpFile::open("fixtures/hibp.txt")?;
+        let entries = audit_passwords(
+            "alice2024\r\n\nLanguage-Shrimp-Fatal-Buffalo\n",
+            &["alice"],
+            false,
+            Some(&mut hibp),
+        )?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].line, 3);
+        assert!(entries[0].password.is_none());
+        assert!(entries[0].report.score < entries[1].report.score);
+
+        let table = audit_table(&entries);
+        assert_eq!(table.lines().count(), 3);
+        assert!(!table.contains("alice2024"));
+        assert_eq!(entries[0].breached, Some(3));
+        assert_eq!(entries[1].breached, Some(0));
+        assert!(entries[0].rejected);
+        assert!(!entries[1].rejected);
+        assert!(table.lines().nth(1).unwrap().contains("rejected"));
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::Result;
+use base64::prelude::*;
+use data_encoding::{BASE32_NOPAD, HEXLOWER_PERMISSIVE};
+use rand::{Rng, RngCore};
+
+use crate::cli::token::{TokenChecksum, TokenEncoding, UuidVersion};
+use crate::utils::{rng, unix_millis};
+
+pub(crate) const CROCKFORD: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+pub const NANOID_ALPHABET: &str =
+    "_-0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const CHECKSUM_LEN: usize = 4;
+
+pub fn generate_uuid(version: UuidVersion) -> Result<String> {
+    let mut bytes = [0u8; 16];
+    rng().fill_bytes(&mut bytes);
+    match version {
+        UuidVersion::V4 => bytes[6] = (bytes[6] & 0x0f) | 0x40,
+        UuidVersion::V7 => {
+            bytes[..6].copy_from_slice(&unix_millis()?.to_be_bytes()[2..]);
+            bytes[6] = (bytes[6] & 0x0f) | 0x70;
+        }
+    }
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = HEXLOWER_PERMISSIVE.encode(&bytes);
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+pub fn generate_ulid() -> Result<String> {
+    let mut random = [0u8; 10];
+    rng().fill_bytes(&mut random);
+    let mut value = (unix_millis()? as u128) << 80;
+    for (i, b) in random.iter().enumerate() {
+        value |= (*b as u128) << (72 - 8 * i);
+    }
+    // 26 characters of 5 bits each, the first one only carries 3 bits
+    Ok((0..26)
+        .rev()
+        .map(|i| CROCKFORD[((value >> (5 * i)) & 0x1f) as usize] as char)
+        .collect())
+}
+
+pub fn generate_nanoid(len: usize, alphabet: &str) -> Result<String> {
+    let alphabet = alphabet.chars().collect::<Vec<_>>();
+    if alphabet.len() < 2 {
+        return Err(anyhow::anyhow!(
+            "alphabet must contain at least 2 characters"
+        ));
+    }
+    // a repeated character would be drawn more often than the others
+    let mut seen = std::collections::HashSet::new();
+    if let Some(c) = alphabet.iter().find(|c| !seen.insert(**c)) {
+        return Err(anyhow::anyhow!("alphabet repeats the character {:?}", c));
+    }
+    Ok((0..len)
+        .map(|_| alphabet[rng().gen_range(0..alphabet.len())])
+        .collect())
+}
+
+fn checksum(algorithm: TokenChecksum, prefix: &str, data: &[u8]) -> Vec<u8> {
+    match algorithm {
+        TokenChecksum::Crc32 => {
+            let mut hasher = crc32fast::Hasher::new();
+            hasher.update(prefix.as_bytes());
+            hasher.update(data);
+            hasher.finalize().to_be_bytes().to_vec()
+        }
+        TokenChecksum::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update(prefix.as_bytes());
+            hasher.update(data);
+            hasher.finalize().as_bytes()[..CHECKSUM_LEN].to_vec()
+        }
+    }
+}
+
+fn encode(encoding: TokenEncoding, data: &[u8]) -> String {
+    match encoding {
+        TokenEncoding::Hex => HEXLOWER_PERMISSIVE.encode(data),
+        TokenEncoding::Base32 => BASE32_NOPAD.encode(data).to_lowercase(),
+        TokenEncoding::Base58 => bs58::encode(data).into_string(),
+        TokenEncoding::Base64url => BASE64_URL_SAFE_NO_PAD.encode(data),
+    }
+}
+
+fn decode(encoding: TokenEncoding, data: &str) -> Option<Vec<u8>> {
+    match encoding {
+        TokenEncoding::Hex => HEXLOWER_PERMISSIVE.decode(data.as_bytes()).ok(),
+        TokenEncoding::Base32 => BASE32_NOPAD.decode(data.to_uppercase().as_bytes()).ok(),
+        TokenEncoding::Base58 => bs58::decode(data).into_vec().ok(),
+        TokenEncoding::Base64url => BASE64_URL_SAFE_NO_PAD.decode(data).ok(),
+    }
+}
+
+// `<prefix>_<encode(random || checksum(prefix || random))>`
+pub fn generate_api_key(
+    prefix: &str,
+    bytes: usize,
+    encoding: TokenEncoding,
+    algorithm: TokenChecksum,
+) -> Result<String> {
+    if bytes < 16 {
+        return Err(anyhow::anyhow!("api keys need at least 16 random bytes"));
+    }
+    let mut data = vec![0u8; bytes];
+    rng().fill_bytes(&mut data);
+    let sum = checksum(algorithm, prefix, &data);
+    data.extend(sum);
+    let body = encode(encoding, &data);
+    Ok(if prefix.is_empty() {
+        body
+    } else {
+        format!("{}_{}", prefix, body)
+    })
+}
+
+// base64url bodies may contain `_`, so every split point is tried
+pub fn check_api_key(token: &str) -> Option<(String, TokenEncoding, TokenChecksum)> {
+    let splits = std::iter::once(("", token)).chain(
+        token
+            .match_indices('_')
+            .map(|(i, _)| (&token[..i], &token[i + 1..])),
+    );
+    for (prefix, body) in splits {
+        for encoding in [
+            TokenEncoding::Hex,
+            TokenEncoding::Base32,
+            TokenEncoding::Base58,
+            TokenEncoding::Base64url,
+        ] {
+            let Some(data) = decode(encoding, body) else {
+                continue;
+            };
+            if data.len() <= CHECKSUM_LEN || encode(encoding, &data) != body {
+                continue;
+            }
+            let (data, sum) = data.split_at(data.len() - CHECKSUM_LEN);
+            for algorithm in [TokenChecksum::Crc32, TokenChecksum::Blake3] {
+                if checksum(algorithm, prefix, data) == sum {
+                    return Some((prefix.to_string(), encoding, algorithm));
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uuid_and_ulid() -> Result<()> {
+        let v4 = generate_uuid(UuidVersion::V4)?;
+        let v7 = generate_uuid(UuidVersion::V7)?;
+        assert_eq!(v4.len(), 36);
+        assert_eq!(&v4[14..15], "4");
+        assert_eq!(&v7[14..15], "7");
+        assert!(matches!(&v7[19..20], "8" | "9" | "a" | "b"));
+
+        let ulid = generate_ulid()?;
+        assert_eq!(ulid.len(), 26);
+        assert!(ulid.bytes().all(|c| CROCKFORD.contains(&c)));
+        assert!(ulid.as_bytes()[0] <= b'7');
+
+        let nanoid = generate_nanoid(21, NANOID_ALPHABET)?;
+        assert_eq!(nanoid.len(), 21);
+        assert!(generate_nanoid(21, "abca").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_api_key_checksum() -> Result<()> {
+        for encoding in [
+            TokenEncoding::Hex,
+            TokenEncoding::Base32,
+            TokenEncoding::Base58,
+            TokenEncoding::Base64url,
+        ] {
+            for algorithm in [TokenChecksum::Crc32, TokenChecksum::Blake3] {
+                let key = generate_api_key("rk_live", 24, encoding, algorithm)?;
+                assert!(key.starts_with("rk_live_"));
+                let (prefix, _, _) = check_api_key(&key).expect("checksum must match");
+                assert_eq!(prefix, "rk_live");
+
+                let mut tampered = key.into_bytes();
+                let last = tampered.len() - 10;
+                tampered[last] = if tampered[last] == b'2' { b'3' } else { b'2' };
+                assert!(check_api_key(&String::from_utf8(tampered)?).is_none());
+            }
+        }
+        Ok(())
+    }
+}
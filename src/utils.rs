@@ -1,8 +1,17 @@
+use rand::{rngs::OsRng, CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use std::{
     fs::File,
-    io::{self, Read},
+    io::{self, IsTerminal, Read, Write},
+    sync::{Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
+static SEEDED_RNG: OnceLock<Mutex<ChaCha20Rng>> = OnceLock::new();
+
+// 2024-11-01T00:00:00Z, the clock UUIDv7 and ULID see once a seed is set
+const SEED_EPOCH_MILLIS: u64 = 1_730_419_200_000;
+
 pub fn get_reader(input: &str) -> anyhow::Result<Box<dyn Read>> {
     let reader: Box<dyn Read> = if input == "-" {
         Box::new(io::stdin())
@@ -12,6 +21,14 @@ pub fn get_reader(input: &str) -> anyhow::Result<Box<dyn Read>> {
     Ok(reader)
 }
 
+pub fn get_writer(output: Option<&str>) -> anyhow::Result<Box<dyn Write>> {
+    let writer: Box<dyn Write> = match output {
+        Some(output) if output != "-" => Box::new(File::create(output)?),
+        _ => Box::new(io::stdout().lock()),
+    };
+    Ok(writer)
+}
+
 pub fn get_content(input: &str) -> anyhow::Result<Vec<u8>> {
     let mut reader = get_reader(input)?;
 
@@ -19,3 +36,86 @@ pub fn get_content(input: &str) -> anyhow::Result<Vec<u8>> {
     reader.read_to_end(&mut content)?;
     Ok(content)
 }
+
+pub fn read_secret(input: Option<&str>, prompt: &str) -> anyhow::Result<String> {
+    let secret = match input {
+        Some(input) => String::from_utf8(get_content(input)?)?,
+        None if io::stdin().is_terminal() => rpassword::prompt_password(prompt)?,
+        None => String::from_utf8(get_content("-")?)?,
+    };
+    Ok(secret)
+}
+
+pub fn seeded_rng(seed: &str) -> ChaCha20Rng {
+    ChaCha20Rng::from_seed(blake3::derive_key(
+        "rcli 2024-11-01 rng seed",
+        seed.as_bytes(),
+    ))
+}
+
+// switch every later `rng()` call onto a ChaCha20 stream derived from `seed`
+pub fn set_seed(seed: &str) -> anyhow::Result<()> {
+    SEEDED_RNG
+        .set(Mutex::new(seeded_rng(seed)))
+        .map_err(|_| anyhow::anyhow!("seed is already set"))
+}
+
+// timestamps for generated ids, pinned under `--seed` so they reproduce byte-for-byte
+pub fn unix_millis() -> anyhow::Result<u64> {
+    if SEEDED_RNG.get().is_some() {
+        return Ok(SEED_EPOCH_MILLIS);
+    }
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64)
+}
+
+pub fn rng() -> CliRng {
+    CliRng
+}
+
+// the OS CSPRNG, unless a seed was set with `set_seed`
+#[derive(Debug, Clone, Copy)]
+pub struct CliRng;
+
+impl CliRng {
+    fn with<T>(&mut self, f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        match SEEDED_RNG.get() {
+            Some(rng) => f(&mut *rng.lock().unwrap_or_else(|e| e.into_inner())),
+            None => f(&mut OsRng),
+        }
+    }
+}
+
+impl RngCore for CliRng {
+    fn next_u32(&mut self) -> u32 {
+        self.with(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.with(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.with(|rng| rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.with(|rng| rng.try_fill_bytes(dest))
+    }
+}
+
+impl CryptoRng for CliRng {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_rng() {
+        let mut a = seeded_rng("fixtures");
+        let mut b = seeded_rng("fixtures");
+        let mut c = seeded_rng("other");
+        let (x, y, z) = (a.next_u64(), b.next_u64(), c.next_u64());
+        assert_eq!(x, y);
+        assert_ne!(x, z);
+    }
+}
@@ -0,0 +1,39 @@
+use std::process::Command;
+
+fn check(passwords: &str, format: &str) -> std::process::Output {
+    let input = std::env::temp_dir().join(format!("rcli-check-{}-{}", std::process::id(), format));
+    std::fs::write(&input, passwords).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rcli"))
+        .args([
+            "genpass",
+            "check",
+            "--hibp-file",
+            "fixtures/hibp.txt",
+            "--format",
+            format,
+        ])
+        .arg("--input")
+        .arg(&input)
+        .output()
+        .expect("failed to run rcli");
+    std::fs::remove_file(&input).unwrap();
+    output
+}
+
+#[test]
+fn test_check_rejects_breached_passwords() {
+    let output = check("alice2024\nLanguage-Shrimp-Fatal-Buffalo\n", "table");
+    assert!(!output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.lines().nth(1).unwrap().contains("rejected"));
+    assert!(stdout.lines().nth(2).unwrap().contains("ok"));
+
+    let output = check("alice2024\n", "json");
+    assert!(!output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\"rejected\": true"));
+
+    assert!(check("Language-Shrimp-Fatal-Buffalo\n", "table")
+        .status
+        .success());
+}
@@ -0,0 +1,44 @@
+use std::process::Command;
+
+fn rcli(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_rcli"))
+        .args(args)
+        .output()
+        .expect("failed to run rcli");
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).expect("output is utf8")
+}
+
+#[test]
+fn test_seed_is_reproducible() {
+    for args in [
+        ["genpass", "--len", "32"].as_slice(),
+        &["token", "uuid", "--kind", "v7"],
+        &["token", "ulid"],
+        &["random", "bytes", "--bytes", "16"],
+    ] {
+        let seeded = |seed| rcli(&[&["--seed", seed], args].concat());
+        assert_eq!(seeded("fixtures"), seeded("fixtures"), "{:?}", args);
+        assert_ne!(seeded("fixtures"), seeded("other"), "{:?}", args);
+    }
+    assert_ne!(rcli(&["token", "ulid"]), rcli(&["token", "ulid"]));
+}
+
+#[test]
+fn test_seed_does_not_apply_to_keys() {
+    let dir = std::env::temp_dir().join(format!("rcli-seed-{}", std::process::id()));
+    let keys = ["a", "b"].map(|name| {
+        let out = dir.join(name);
+        std::fs::create_dir_all(&out).unwrap();
+        let out = out.to_str().unwrap();
+        for format in ["ed25519", "blake3"] {
+            rcli(&[
+                "--seed", "fixtures", "text", "generate", "--format", format, "--output", out,
+            ]);
+        }
+        ["ed25519.sk", "blake3.txt"].map(|file| std::fs::read(dir.join(name).join(file)).unwrap())
+    });
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_ne!(keys[0][0], keys[1][0]);
+    assert_ne!(keys[0][1], keys[1][1]);
+}